@@ -0,0 +1,10 @@
+#!/usr/bin/env sh
+# Replaces the vendored GitHub GraphQL schema with the one GitHub publishes, so the generated
+# queries are checked against the real API. Run `cargo build` afterwards: a query that selects or
+# sends something the API doesn't have fails to compile.
+set -eu
+
+cd "$(dirname "$0")/.."
+curl --fail --silent --show-error --location \
+  --output schemas/github.graphql \
+  https://docs.github.com/public/fpt/schema.docs.graphql
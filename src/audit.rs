@@ -0,0 +1,432 @@
+use std::{fmt::Display, fmt::Write as _};
+
+use serde::{Deserialize, Serialize};
+
+use crate::item::{self, ProjectItem};
+use crate::models::{Issue, Params, SingleSelectField, SingleSelectOption};
+use crate::output::Outputs;
+use crate::queries::Client;
+use crate::reconcile;
+use crate::repositories;
+use crate::summary::Summary;
+use crate::{field, project};
+
+/// Why the value of a project item is wrong.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum ItemProblem {
+  /// The option of the value is a dangling option.
+  DanglingOption,
+  /// The item is the epic of another option than the one of its value.
+  OtherEpic { name: String },
+}
+
+impl Display for ItemProblem {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ItemProblem::DanglingOption => write!(f, "the option has no epic issue"),
+      ItemProblem::OtherEpic { name } => write!(f, "the item is the epic of \"{name}\""),
+    }
+  }
+}
+
+/// Why an option of an epic has no backing epic issue.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "problem", rename_all = "snake_case")]
+pub enum OptionProblem {
+  /// The issue is not in the repository, because it was deleted or transferred.
+  IssueNotFound,
+  /// The issue no longer matches the label filter and criteria.
+  NoLongerEpic { number: u32 },
+  /// The issue is closed and the close policy removes its option.
+  Closed { number: u32 },
+}
+
+impl Display for OptionProblem {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OptionProblem::IssueNotFound => write!(f, "the issue was deleted or transferred"),
+      OptionProblem::NoLongerEpic { number } => write!(f, "issue #{number} is no longer an epic"),
+      OptionProblem::Closed { number } => write!(f, "issue #{number} is closed"),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OrphanedItem {
+  item: ProjectItem,
+  #[serde(flatten)]
+  problem: ItemProblem,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DanglingOption {
+  option: SingleSelectOption,
+  #[serde(flatten)]
+  problem: OptionProblem,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MissingOption {
+  number: u32,
+  title: String,
+  url: String,
+  option: SingleSelectOption,
+}
+
+/// Cross-references the items of the project, the options of the field and the epics of the
+/// repositories.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Report {
+  /// Items whose value is a dangling option, or whose issue has another option than its value.
+  orphaned_items: Vec<OrphanedItem>,
+  /// Options of epics without a backing epic issue.
+  dangling_options: Vec<DanglingOption>,
+  /// Epic issues without an option.
+  missing_options: Vec<MissingOption>,
+}
+
+impl Report {
+  /// Options of issues outside the `repositories` are not checked.
+  pub fn compute(
+    params: &Params,
+    repositories: &[String],
+    field: &SingleSelectField,
+    items: &[ProjectItem],
+    issues: &[Issue],
+  ) -> Self {
+    let dangling_options = field
+      .options()
+      .iter()
+      .filter(|option| option.is_in(repositories))
+      .filter_map(|option| {
+        let issue_id = option.issue_id()?;
+        let problem = match issues.iter().find(|i| i.id() == issue_id) {
+          None => OptionProblem::IssueNotFound,
+          Some(issue) if !params.is_epic(issue) => OptionProblem::NoLongerEpic {
+            number: issue.number(),
+          },
+          Some(issue) if SingleSelectOption::desired(issue, params.on_close(), &[]).is_none() => {
+            OptionProblem::Closed {
+              number: issue.number(),
+            }
+          }
+          Some(_) => return None,
+        };
+
+        Some(DanglingOption {
+          option: option.clone(),
+          problem,
+        })
+      })
+      .collect::<Vec<_>>();
+
+    // GitHub clears the values of removed options and returns the current names, so a value can
+    // only be wrong by pointing at a dangling option or at another option than the item's own.
+    let orphaned_items = items
+      .iter()
+      .filter_map(|item| {
+        let own = item
+          .content_id()
+          .and_then(|id| field.options().iter().find(|o| o.issue_id() == Some(id)));
+        let problem = if dangling_options
+          .iter()
+          .any(|d| d.option.id() == Some(item.option_id()))
+        {
+          ItemProblem::DanglingOption
+        } else {
+          match own {
+            Some(own) if own.id() != Some(item.option_id()) => ItemProblem::OtherEpic {
+              name: own.name().to_string(),
+            },
+            _ => return None,
+          }
+        };
+
+        Some(OrphanedItem {
+          item: item.clone(),
+          problem,
+        })
+      })
+      .collect();
+
+    let missing_options = reconcile::desired_options(params, issues, field.options())
+      .into_iter()
+      .filter(|option| {
+        !field
+          .options()
+          .iter()
+          .any(|o| o.issue_id() == option.issue_id())
+      })
+      .filter_map(|option| {
+        let issue = issues.iter().find(|i| Some(i.id()) == option.issue_id())?;
+
+        Some(MissingOption {
+          number: issue.number(),
+          title: issue.title().to_string(),
+          url: issue.html_url().to_string(),
+          option,
+        })
+      })
+      .collect();
+
+    Self {
+      orphaned_items,
+      dangling_options,
+      missing_options,
+    }
+  }
+
+  pub fn findings(&self) -> usize {
+    self.orphaned_items.len() + self.dangling_options.len() + self.missing_options.len()
+  }
+
+  /// The findings as Markdown tables.
+  pub fn markdown(&self) -> String {
+    let escape = |s: &str| s.replace('|', "\\|");
+    let mut markdown = String::from("#### Orphaned items\n\n");
+
+    if self.orphaned_items.is_empty() {
+      markdown.push_str("None\n");
+    } else {
+      markdown.push_str("| Item | Value | Problem |\n| --- | --- | --- |\n");
+      for orphaned in &self.orphaned_items {
+        let item = &orphaned.item;
+        let title = match item.url() {
+          Some(url) => format!("[{}]({url})", escape(item.title())),
+          // Items without content, e.g. issues of repositories we can't read.
+          None if item.title().is_empty() => format!("`{}`", item.id()),
+          None => escape(item.title()),
+        };
+        let _ = writeln!(
+          markdown,
+          "| {title} | {} | {} |",
+          escape(item.option_name()),
+          escape(&orphaned.problem.to_string())
+        );
+      }
+    }
+
+    markdown.push_str("\n#### Dangling options\n\n");
+    if self.dangling_options.is_empty() {
+      markdown.push_str("None\n");
+    } else {
+      markdown.push_str("| Option | Problem |\n| --- | --- |\n");
+      for dangling in &self.dangling_options {
+        let _ = writeln!(
+          markdown,
+          "| {} | {} |",
+          escape(dangling.option.name()),
+          dangling.problem
+        );
+      }
+    }
+
+    markdown.push_str("\n#### Epics without option\n\n");
+    if self.missing_options.is_empty() {
+      markdown.push_str("None\n");
+    } else {
+      markdown.push_str("| Issue | Option |\n| --- | --- |\n");
+      for missing in &self.missing_options {
+        let _ = writeln!(
+          markdown,
+          "| [#{} {}]({}) | {} |",
+          missing.number,
+          escape(&missing.title),
+          missing.url,
+          escape(missing.option.name())
+        );
+      }
+    }
+
+    markdown
+  }
+}
+
+/// Reports the project items, options and epics of the repositories that are out of sync with
+/// each other, without changing anything.
+pub async fn run(
+  params: &Params,
+  client: &Client,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  let repositories = repositories::resolve(params, client).await?;
+
+  let project_id = project::resolve(client, params.project()).await?;
+  outputs.set("project-id", &project_id);
+  summary.project(params, &project_id);
+
+  let field = field::find(client, &project_id, params.field_name()).await?;
+  outputs.set("field-id", field.id());
+  summary.field(&field);
+
+  let items = item::list_with_value(client, &project_id, &field).await?;
+  let issues = repositories::list_issues(client, &repositories).await?;
+
+  let report = Report::compute(params, &repositories, &field, &items, &issues);
+  outputs
+    .set("findings", report.findings())
+    .set("audit", serde_json::to_string(&report)?);
+  summary.audit(&report);
+
+  println!("{}", report.markdown());
+  println!(
+    "Found {} problems with {} items and {} options of field \"{}\"",
+    report.findings(),
+    items.len(),
+    field.options().len(),
+    field.name()
+  );
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use crate::fixtures::{field, issue_with, option, params, REPOSITORY};
+  use crate::item::ProjectItem;
+  use crate::models::{Color, Issue, Params};
+
+  use super::{ItemProblem, OptionProblem, Report};
+
+  fn close_removes() -> Params {
+    params(&["epic", "", "", "remove"])
+  }
+
+  fn issue(id: u64, title: &str, label: &str, state: &str) -> Issue {
+    issue_with(id, id as u32, title, &[label], state)
+  }
+
+  fn item(id: &str, content_id: u64, option_id: &str, option_name: &str) -> ProjectItem {
+    ProjectItem::new(
+      id.to_string(),
+      format!("Task {id}"),
+      None,
+      Some(content_id),
+      option_id.to_string(),
+      option_name.to_string(),
+    )
+  }
+
+  #[test]
+  fn report_every_problem() -> anyhow::Result<(), anyhow::Error> {
+    let field = field(vec![
+      option(Some("a"), "No epic", Color::Gray, None),
+      option(Some("b"), "Roadmap", Color::Gray, Some((1, 1))),
+      option(Some("c"), "Gone", Color::Gray, Some((2, 2))),
+      option(Some("d"), "Bug", Color::Gray, Some((3, 3))),
+      option(Some("e"), "Done", Color::Gray, Some((4, 4))),
+      option(Some("f"), "Search", Color::Gray, Some((6, 6))),
+    ]);
+    let items = vec![
+      item("1", 10, "b", "Roadmap"),
+      item("2", 11, "c", "Gone"),
+      item("3", 1, "f", "Search"),
+      item("4", 12, "a", "No epic"),
+      item("6", 6, "f", "Search"),
+    ];
+    let issues = vec![
+      issue(1, "Roadmap", "epic", "open"),
+      issue(3, "Bug", "bug", "open"),
+      issue(4, "Done", "epic", "closed"),
+      issue(5, "Login", "epic", "open"),
+      issue(6, "Search", "epic", "open"),
+    ];
+
+    let report = Report::compute(
+      &close_removes(),
+      &[REPOSITORY.to_string()],
+      &field,
+      &items,
+      &issues,
+    );
+
+    assert_eq!(report.findings(), 6);
+    assert_eq!(
+      report
+        .orphaned_items
+        .iter()
+        .map(|o| (o.item.id(), o.problem.clone()))
+        .collect::<Vec<_>>(),
+      vec![
+        ("2", ItemProblem::DanglingOption),
+        (
+          "3",
+          ItemProblem::OtherEpic {
+            name: "Roadmap".to_string()
+          }
+        ),
+      ]
+    );
+    assert_eq!(
+      report
+        .dangling_options
+        .iter()
+        .map(|d| (d.option.name(), d.problem.clone()))
+        .collect::<Vec<_>>(),
+      vec![
+        ("Gone", OptionProblem::IssueNotFound),
+        ("Bug", OptionProblem::NoLongerEpic { number: 3 }),
+        ("Done", OptionProblem::Closed { number: 4 }),
+      ]
+    );
+    assert_eq!(report.missing_options.len(), 1);
+    assert_eq!(report.missing_options[0].option.name(), "Login");
+
+    let json = serde_json::to_value(&report)?;
+    assert_eq!(json["orphaned_items"][1]["problem"], "other_epic");
+    assert_eq!(json["dangling_options"][1]["number"], 3);
+
+    assert_eq!(
+      report.markdown(),
+      "#### Orphaned items
+
+| Item | Value | Problem |
+| --- | --- | --- |
+| Task 2 | Gone | the option has no epic issue |
+| Task 3 | Search | the item is the epic of \"Roadmap\" |
+
+#### Dangling options
+
+| Option | Problem |
+| --- | --- |
+| Gone | the issue was deleted or transferred |
+| Bug | issue #3 is no longer an epic |
+| Done | issue #4 is closed |
+
+#### Epics without option
+
+| Issue | Option |
+| --- | --- |
+| [#5 Login](https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/5) | Login |
+"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn report_nothing_when_in_sync() {
+    let field = field(vec![option(
+      Some("b"),
+      "Roadmap",
+      Color::Gray,
+      Some((1, 1)),
+    )]);
+
+    let report = Report::compute(
+      &close_removes(),
+      &[REPOSITORY.to_string()],
+      &field,
+      &[item("1", 1, "b", "Roadmap"), item("2", 10, "b", "Roadmap")],
+      &[issue(1, "Roadmap", "epic", "open")],
+    );
+
+    assert_eq!(report, Report::default());
+    assert_eq!(
+      report.markdown(),
+      "#### Orphaned items\n\nNone\n\n#### Dangling options\n\nNone\n\n#### Epics without option\n\nNone\n"
+    );
+  }
+}
@@ -0,0 +1,108 @@
+use std::env;
+
+use anyhow::Context;
+
+use crate::models::{Params, SingleSelectField, SingleSelectOption};
+use crate::output::Outputs;
+use crate::queries::Client;
+use crate::summary::Summary;
+use crate::{field, issues, options, project};
+
+/// Counts of a backfill run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+  pub pages: u32,
+  pub issues: usize,
+  pub epics: usize,
+  pub created: usize,
+}
+
+/// Pages through the issues of the repository in `GITHUB_REPOSITORY` and creates the missing
+/// options of all epics in batches. The next page is written to the `next-page` output after
+/// every page, so an interrupted backfill can be resumed with the `start-page` input. Options
+/// that already exist are skipped, so pages can be processed twice.
+pub async fn run(
+  params: &Params,
+  client: &Client,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  let repository = env::var("GITHUB_REPOSITORY").context("Missing GITHUB_REPOSITORY")?;
+
+  let project_id = project::resolve(client, params.project()).await?;
+  outputs.set("project-id", &project_id);
+  summary.project(params, &project_id);
+
+  let mut field = field::find(client, &project_id, params.field_name()).await?;
+  outputs.set("field-id", field.id());
+  summary.field(&field);
+
+  let mut progress = Progress::default();
+  let mut page = params.start_page();
+
+  let result = loop {
+    match backfill_page(params, client, &repository, &mut field, page).await {
+      Ok(Some(counts)) => {
+        progress.pages += 1;
+        progress.issues += counts.issues;
+        progress.epics += counts.epics;
+        progress.created += counts.created;
+        page += 1;
+        outputs.set("next-page", page);
+
+        println!(
+          "Backfilled page {} of {repository}: {} issues, {} epics, {} missing options created ({} created in total)",
+          page - 1,
+          counts.issues,
+          counts.epics,
+          counts.created,
+          progress.created
+        );
+      }
+      Ok(None) => break Ok(()),
+      Err(error) => break Err(error),
+    }
+  };
+
+  outputs.set("created-options", progress.created);
+  summary.backfill(&repository, &progress);
+
+  result
+}
+
+/// Creates the missing options of the epics on one page, `None` after the last page.
+async fn backfill_page(
+  params: &Params,
+  client: &Client,
+  repository: &str,
+  field: &mut SingleSelectField,
+  page: u32,
+) -> anyhow::Result<Option<Progress>, anyhow::Error> {
+  let issues = issues::list(client, repository, params.state(), page).await?;
+  if issues.is_empty() {
+    return Ok(None);
+  }
+
+  let epics = issues
+    .iter()
+    .filter(|issue| !issue.is_pull_request() && params.is_epic(issue))
+    .collect::<Vec<_>>();
+  let mut others = field.options().clone();
+  others.extend(epics.iter().map(|i| SingleSelectOption::for_issue(i, &[])));
+  let missing = epics
+    .iter()
+    .filter(|issue| field.option_for(issue).is_none())
+    .filter_map(|issue| SingleSelectOption::desired(issue, params.on_close(), &others))
+    .collect::<Vec<_>>();
+
+  for batch in missing.chunks(params.batch_size()) {
+    *field = options::add_all(client, field, batch).await?;
+  }
+
+  Ok(Some(Progress {
+    pages: 1,
+    issues: issues.len(),
+    epics: epics.len(),
+    created: missing.len(),
+  }))
+}
@@ -5,6 +5,7 @@ use graphql_client::{GraphQLQuery, Response};
 
 macro_rules! generate_query {
   ($query:ident) => {
+    #[allow(dead_code)]
     #[derive(GraphQLQuery)]
     #[graphql(
       schema_path = "schemas/github.graphql",
@@ -0,0 +1,500 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::Context;
+
+use crate::errors;
+use crate::models::{CommentAction, IssueCommentEvent, Params, Repository};
+use crate::output::Outputs;
+use crate::queries::{
+  self, add_comment, add_reaction, get_permission, AddComment, AddReaction, Client, GetPermission,
+};
+use crate::summary::Summary;
+use crate::{field, issues, options, project};
+
+const PREFIX: &str = "/epic";
+
+/// A slash command in an issue comment, e.g. `/epic rename Roadmap 2024`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+  Add,
+  Remove,
+  Rename(String),
+}
+
+impl Command {
+  /// Parses the first line of the comment that starts with `/epic`, if there is one.
+  pub fn find(body: &str) -> Option<anyhow::Result<Self, anyhow::Error>> {
+    body
+      .lines()
+      .map(str::trim)
+      .find(|line| line.split_whitespace().next() == Some(PREFIX))
+      .map(Self::from_str)
+  }
+}
+
+impl FromStr for Command {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let s = s.trim();
+    let arguments = s.strip_prefix(PREFIX).unwrap_or(s).trim();
+    let (command, argument) = arguments
+      .split_once(char::is_whitespace)
+      .map(|(c, a)| (c, a.trim()))
+      .unwrap_or((arguments, ""));
+
+    match command.to_lowercase().as_str() {
+      "add" => Ok(Self::Add),
+      "remove" => Ok(Self::Remove),
+      "rename" if argument.is_empty() => {
+        Err(errors::Errors::MissingCommandArgument(s.to_string(), "name".to_string()).into())
+      }
+      "rename" => Ok(Self::Rename(argument.to_string())),
+      _ => Err(errors::Errors::UnknownCommand(s.to_string()).into()),
+    }
+  }
+}
+
+impl Display for Command {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Command::Add => write!(f, "{PREFIX} add"),
+      Command::Remove => write!(f, "{PREFIX} remove"),
+      Command::Rename(name) => write!(f, "{PREFIX} rename {name}"),
+    }
+  }
+}
+
+/// Runs the `/epic` command of a new comment and reacts to the comment with the result. Only
+/// users with write permission get a reaction or a reply, also for invalid commands, so nobody
+/// else can make the action comment. Commands of other users are only logged.
+pub async fn run(
+  params: &Params,
+  client: &Client,
+  event: &IssueCommentEvent,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  let issue = event.issue();
+  let comment = event.comment();
+
+  if event.action() != &CommentAction::Created {
+    println!(
+      "Nothing to do for edited or deleted comments on issue #{}",
+      issue.number()
+    );
+    return Ok(());
+  }
+
+  let Some(command) = Command::find(comment.body()) else {
+    println!(
+      "The comment on issue #{} contains no {PREFIX} command",
+      issue.number()
+    );
+    return Ok(());
+  };
+
+  let login = comment.user().login();
+  let repository = event
+    .repository()
+    .context("The issue_comment event has no repository")?;
+  if !has_write_permission(client, repository, login).await? {
+    println!(
+      "Ignored the command on issue #{}: {}",
+      issue.number(),
+      errors::Errors::NoCommandPermission(login.to_string(), repository.full_name().to_string())
+    );
+    return Ok(());
+  }
+
+  let command = match command {
+    Ok(command) => command,
+    Err(error) => return reject(client, event, &error).await,
+  };
+  outputs.set("command", &command);
+  summary.command(issue, &command, login);
+
+  match execute(params, client, event, &command, outputs, summary).await {
+    Ok(()) => {
+      react(
+        client,
+        comment.node_id(),
+        add_reaction::ReactionContent::THUMBS_UP,
+      )
+      .await
+    }
+    Err(error) => {
+      react(
+        client,
+        comment.node_id(),
+        add_reaction::ReactionContent::THUMBS_DOWN,
+      )
+      .await?;
+      reply(
+        client,
+        issue.node_id(),
+        &format!("`{command}` failed: {error}"),
+      )
+      .await?;
+      Err(error)
+    }
+  }
+}
+
+async fn execute(
+  params: &Params,
+  client: &Client,
+  event: &IssueCommentEvent,
+  command: &Command,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  // The options of pull requests are keyed by the id of the pull request, not of its issue.
+  let pull_request;
+  let issue = match event.issue() {
+    issue if issue.is_pull_request() => {
+      let repository = event
+        .repository()
+        .context("The issue_comment event has no repository")?;
+      pull_request =
+        issues::get_pull_request(client, repository.full_name(), issue.number()).await?;
+      &pull_request
+    }
+    issue => issue,
+  };
+
+  let project_id = project::resolve(client, params.project()).await?;
+  outputs.set("project-id", &project_id);
+  summary.project(params, &project_id);
+
+  let field = field::find(client, &project_id, params.field_name()).await?;
+  outputs.set("field-id", field.id());
+  summary.field(&field);
+
+  let operation = match command {
+    Command::Add => options::add(client, &field, issue).await?,
+    Command::Remove => options::remove(client, &field, issue).await?,
+    Command::Rename(name) => options::rename_to(client, &field, issue, name).await?,
+  };
+
+  let option = operation.option();
+  outputs
+    .set("option-id", option.id().unwrap_or_default())
+    .set("option-name", option.name())
+    .set("operation", &operation);
+  summary.operation(&operation);
+
+  println!(
+    "{command} on issue #{}: option \"{}\" {}",
+    issue.number(),
+    option.name(),
+    operation
+  );
+
+  Ok(())
+}
+
+/// Whether the user can push to the repository, which is required to run commands.
+async fn has_write_permission(
+  client: &Client,
+  repository: &Repository,
+  login: &str,
+) -> anyhow::Result<bool, anyhow::Error> {
+  use get_permission::*;
+
+  let data = queries::post::<GetPermission>(
+    client,
+    Variables {
+      owner: repository.owner().login().to_string(),
+      name: repository.name().to_string(),
+      login: login.to_string(),
+    },
+  )
+  .await?;
+
+  let permission = data
+    .repository
+    .and_then(|r| r.collaborators)
+    .and_then(|c| c.edges)
+    .unwrap_or_default()
+    .into_iter()
+    .flatten()
+    .find(|edge| edge.node.login.eq_ignore_ascii_case(login))
+    .map(|edge| edge.permission);
+
+  Ok(matches!(
+    permission,
+    Some(
+      RepositoryPermission::ADMIN | RepositoryPermission::MAINTAIN | RepositoryPermission::WRITE
+    )
+  ))
+}
+
+async fn reject(
+  client: &Client,
+  event: &IssueCommentEvent,
+  error: &anyhow::Error,
+) -> anyhow::Result<(), anyhow::Error> {
+  println!(
+    "Rejected the command on issue #{}: {error}",
+    event.issue().number()
+  );
+
+  react(
+    client,
+    event.comment().node_id(),
+    add_reaction::ReactionContent::CONFUSED,
+  )
+  .await?;
+  reply(
+    client,
+    event.issue().node_id(),
+    &format!("@{} {error}", event.comment().user().login()),
+  )
+  .await
+}
+
+async fn react(
+  client: &Client,
+  comment_id: &str,
+  content: add_reaction::ReactionContent,
+) -> anyhow::Result<(), anyhow::Error> {
+  queries::mutate::<AddReaction>(
+    client,
+    add_reaction::Variables {
+      subject_id: comment_id.to_string(),
+      content,
+    },
+  )
+  .await?;
+
+  Ok(())
+}
+
+async fn reply(client: &Client, issue_id: &str, body: &str) -> anyhow::Result<(), anyhow::Error> {
+  queries::mutate::<AddComment>(
+    client,
+    add_comment::Variables {
+      subject_id: issue_id.to_string(),
+      body: body.to_string(),
+    },
+  )
+  .await?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use std::str::FromStr;
+
+  use crate::errors::Errors;
+  use crate::fake_github::FakeGitHub;
+  use crate::fixtures::params;
+  use crate::models::Event;
+  use crate::output::Outputs;
+  use crate::queries::Client;
+  use crate::summary::Summary;
+
+  use super::Command;
+
+  const ISSUE_COMMENT_EVENT: &str = r#"{
+    "action": "created",
+    "issue": {
+      "id": 1611401201,
+      "node_id": "I_kwDOJEXPh85gDAfx",
+      "labels": [],
+      "number": 19,
+      "title": "Test19",
+      "html_url": "https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/19"
+    },
+    "comment": {
+      "id": 1476913523,
+      "node_id": "IC_kwDOJEXPh85YCCNz",
+      "body": "/epic rename",
+      "user": { "login": "octocat" }
+    },
+    "repository": {
+      "id": 608554887,
+      "node_id": "R_kgDOJEXPhw",
+      "name": "example-add-epic-issue-to-project",
+      "full_name": "JenSeReal/example-add-epic-issue-to-project",
+      "html_url": "https://github.com/JenSeReal/example-add-epic-issue-to-project",
+      "owner": { "login": "JenSeReal" }
+    }
+  }"#;
+
+  fn permission(permission: &str) -> serde_json::Value {
+    serde_json::json!({
+      "data": {
+        "repository": {
+          "collaborators": {
+            "edges": [{ "permission": permission, "node": { "login": "octocat" } }]
+          }
+        }
+      }
+    })
+  }
+
+  async fn run_command(permission: serde_json::Value) -> anyhow::Result<Client, anyhow::Error> {
+    let params = params(&[]);
+    let github = FakeGitHub::start(vec![permission]).await?;
+    let client = github.dry_run_client()?;
+    let Event::IssueComment(event) = Event::parse("issue_comment", ISSUE_COMMENT_EVENT)? else {
+      panic!("Expected an issue_comment event");
+    };
+
+    super::run(
+      &params,
+      &client,
+      &event,
+      &mut Outputs::default(),
+      &mut Summary::default(),
+    )
+    .await?;
+
+    assert_eq!(github.requests().len(), 1);
+    Ok(client)
+  }
+
+  #[test]
+  fn parse_commands() -> anyhow::Result<(), anyhow::Error> {
+    assert_eq!(Command::from_str("/epic add")?, Command::Add);
+    assert_eq!(Command::from_str("/epic REMOVE")?, Command::Remove);
+    assert_eq!(
+      Command::from_str("/epic rename  Roadmap 2024 ")?,
+      Command::Rename("Roadmap 2024".to_string())
+    );
+    assert_eq!(
+      Command::Rename("Roadmap 2024".to_string()).to_string(),
+      "/epic rename Roadmap 2024"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn find_command_in_comment() -> anyhow::Result<(), anyhow::Error> {
+    assert!(Command::find("Looks like an epic to me").is_none());
+    assert!(Command::find("/epics are great").is_none());
+    assert_eq!(
+      Command::find("Looks like an epic to me\n\n  /epic add\n/epic remove").transpose()?,
+      Some(Command::Add)
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn reject_invalid_commands() {
+    let error = |s: &str| {
+      Command::from_str(s)
+        .unwrap_err()
+        .downcast::<Errors>()
+        .unwrap()
+    };
+
+    assert!(matches!(error("/epic"), Errors::UnknownCommand(_)));
+    assert!(matches!(error("/epic delete"), Errors::UnknownCommand(_)));
+    assert!(matches!(
+      error("/epic rename"),
+      Errors::MissingCommandArgument(_, _)
+    ));
+  }
+
+  #[tokio::test]
+  async fn reply_to_invalid_command() -> anyhow::Result<(), anyhow::Error> {
+    let client = run_command(permission("WRITE")).await?;
+
+    let planned = client.planned();
+    assert_eq!(planned.len(), 2);
+    assert_eq!(
+      planned[0]["variables"],
+      serde_json::json!({ "subjectId": "IC_kwDOJEXPh85YCCNz", "content": "CONFUSED" })
+    );
+    assert_eq!(
+      planned[1]["variables"]["body"],
+      "@octocat Command /epic rename needs a name"
+    );
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn ignore_commands_without_permission() -> anyhow::Result<(), anyhow::Error> {
+    let client = run_command(permission("READ")).await?;
+
+    assert!(client.planned().is_empty());
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn use_pull_request_id_for_comments_on_pull_requests() -> anyhow::Result<(), anyhow::Error>
+  {
+    let params = params(&[]);
+    let github = FakeGitHub::start(vec![
+      permission("WRITE"),
+      serde_json::json!({
+        "id": 1270531035,
+        "node_id": "PR_kwDOJEXPh85LuoXb",
+        "labels": [{ "id": 5252885350u64, "name": "epic" }],
+        "number": 19,
+        "title": "Test19",
+        "html_url": "https://github.com/JenSeReal/example-add-epic-issue-to-project/pull/19"
+      }),
+      serde_json::json!({ "data": { "organization": { "projectV2": { "id": "PVT_kwDOA" } } } }),
+      serde_json::json!({
+        "data": {
+          "node": {
+            "__typename": "ProjectV2",
+            "fields": {
+              "pageInfo": { "hasNextPage": false, "endCursor": null },
+              "nodes": [{
+                "__typename": "ProjectV2SingleSelectField",
+                "id": "PVTSSF_epic",
+                "name": "Epic",
+                "options": [{
+                  "id": "a1",
+                  "name": "Test19",
+                  "color": "GREEN",
+                  "description": "JenSeReal/example-add-epic-issue-to-project#19 issue-id:1270531035"
+                }]
+              }]
+            }
+          }
+        }
+      }),
+    ])
+    .await?;
+    let client = github.dry_run_client()?;
+    let event = ISSUE_COMMENT_EVENT
+      .replace("/epic rename", "/epic add")
+      .replace(
+        r#""title": "Test19","#,
+        r#""title": "Test19", "pull_request": {},"#,
+      );
+    let Event::IssueComment(event) = Event::parse("issue_comment", &event)? else {
+      panic!("Expected an issue_comment event");
+    };
+
+    super::run(
+      &params,
+      &client,
+      &event,
+      &mut Outputs::default(),
+      &mut Summary::default(),
+    )
+    .await?;
+
+    assert_eq!(
+      github.paths()[1],
+      "/repos/JenSeReal/example-add-epic-issue-to-project/pulls/19"
+    );
+    let planned = client.planned();
+    assert_eq!(planned.len(), 1);
+    assert_eq!(planned[0]["variables"]["content"], "THUMBS_UP");
+
+    Ok(())
+  }
+}
@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+use crate::errors;
+use crate::models::{AuthorAssociation, Issue};
+use crate::pattern::Pattern;
+
+/// How the label filter and the criteria are combined.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Mode {
+  /// Any configured criterion marks the issue as an epic.
+  #[default]
+  Any,
+  /// All configured criteria have to match.
+  All,
+}
+
+impl FromStr for Mode {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_lowercase().as_str() {
+      "any" => Ok(Self::Any),
+      "all" => Ok(Self::All),
+      _ => Err(errors::Errors::CouldNotParseMode(s.to_string()).into()),
+    }
+  }
+}
+
+/// Issue attributes besides labels that mark an issue as an epic. Criteria that are not
+/// configured are not checked.
+#[derive(Debug, Clone, Default)]
+pub struct Criteria {
+  title: Option<Pattern>,
+  body: Option<Pattern>,
+  milestone: Option<Pattern>,
+  issue_type: Option<Pattern>,
+  assignee: Option<Pattern>,
+  author_associations: Vec<AuthorAssociation>,
+}
+
+impl Criteria {
+  pub fn new(
+    title: Option<Pattern>,
+    body: Option<Pattern>,
+    milestone: Option<Pattern>,
+    issue_type: Option<Pattern>,
+    assignee: Option<Pattern>,
+    author_associations: Vec<AuthorAssociation>,
+  ) -> Self {
+    Self {
+      title,
+      body,
+      milestone,
+      issue_type,
+      assignee,
+      author_associations,
+    }
+  }
+
+  /// The result of every configured criterion for `issue`.
+  pub fn evaluate(&self, issue: &Issue) -> Vec<bool> {
+    let check = |pattern: &Option<Pattern>, value: Option<&str>| {
+      pattern
+        .as_ref()
+        .map(|p| value.map_or(false, |v| p.matches(v)))
+    };
+
+    [
+      check(&self.title, Some(issue.title())),
+      check(&self.body, issue.body()),
+      check(&self.milestone, issue.milestone()),
+      check(&self.issue_type, issue.issue_type()),
+      self
+        .assignee
+        .as_ref()
+        .map(|p| issue.assignees().any(|a| p.matches(a))),
+      (!self.author_associations.is_empty()).then(|| {
+        self
+          .author_associations
+          .contains(issue.author_association())
+      }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::str::FromStr;
+
+  use crate::fixtures::issue;
+  use crate::models::{AuthorAssociation, Issue};
+  use crate::pattern::Pattern;
+
+  use super::Criteria;
+
+  const ISSUE: &str = r#"{
+    "id": 1611401201,
+    "node_id": "I_kwDOJEXPh85gDAfx",
+    "labels": [],
+    "number": 19,
+    "title": "[EPIC] Roadmap",
+    "body": "**Kind**\n\nEpic\n",
+    "milestone": { "title": "v1.0" },
+    "type": { "name": "Epic" },
+    "assignees": [{ "login": "JenSeReal" }],
+    "author_association": "OWNER",
+    "html_url": "https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/19"
+  }"#;
+
+  fn pattern(s: &str) -> Option<Pattern> {
+    Some(Pattern::from_str(s).unwrap())
+  }
+
+  #[test]
+  fn evaluate_configured_criteria() -> anyhow::Result<(), anyhow::Error> {
+    let issue: Issue = serde_json::from_str(ISSUE)?;
+
+    assert!(Criteria::default().evaluate(&issue).is_empty());
+    assert_eq!(
+      Criteria::new(
+        pattern("[EPIC]*"),
+        pattern("/Kind\\*\\*\\s+Epic/"),
+        pattern("v1.*"),
+        pattern("Epic"),
+        pattern("octocat"),
+        vec![AuthorAssociation::Member, AuthorAssociation::Owner],
+      )
+      .evaluate(&issue),
+      [true, true, true, true, false, true]
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn missing_attributes_do_not_match() {
+    let issue = issue(&[]);
+
+    assert_eq!(
+      Criteria::new(
+        None,
+        pattern("/.*/"),
+        pattern("*"),
+        pattern("*"),
+        pattern("*"),
+        vec![AuthorAssociation::None],
+      )
+      .evaluate(&issue),
+      [false, false, false, false, true]
+    );
+  }
+}
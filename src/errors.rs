@@ -2,6 +2,70 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Errors {
+  #[error("Missing required input {0}")]
+  MissingInput(String),
+  #[error("{} invalid inputs: {}", .0.len(), .0.iter().map(|(input, error)| format!("{input}: {error}")).collect::<Vec<_>>().join("; "))]
+  InvalidInputs(Vec<(String, String)>),
+  #[error("Could not parse project url {0}: {1}. Expected https://github.com/orgs/<owner>/projects/<number> or https://github.com/users/<owner>/projects/<number>")]
+  InvalidProjectUrl(String, String),
+  #[error("Could not parse boolean {0}. Possible values are true and false")]
+  CouldNotParseBoolean(String),
+  #[error("Could not parse mode {0}. Possible values are backfill, sync, plan, apply and audit")]
+  CouldNotParseRunMode(String),
+  #[error("Could not parse repository {0}. Expected <owner>/<name> or <name> of a repository of the project owner")]
+  CouldNotParseRepository(String),
+  #[error("Could not parse state {0}. Possible values are open, closed and all")]
+  CouldNotParseState(String),
+  #[error("Could not parse {0} as a positive number")]
+  CouldNotParseNumber(String),
   #[error("Could not parse operator {0}. Possible values are AND, OR and NOT")]
   CouldNotParseOperator(String),
+  #[error("Could not parse close policy {0}. Possible values are keep, rename-with-suffix, recolor and remove")]
+  CouldNotParseClosePolicy(String),
+  #[error("Could not parse match mode {0}. Possible values are any and all")]
+  CouldNotParseMode(String),
+  #[error("Could not parse author association {0}. Possible values are OWNER, MEMBER, COLLABORATOR, CONTRIBUTOR, FIRST_TIME_CONTRIBUTOR, FIRST_TIMER, MANNEQUIN and NONE")]
+  CouldNotParseAuthorAssociation(String),
+  #[error("Unexpected {1} at column {0} of the label expression, expected {2}")]
+  UnexpectedToken(usize, String, String),
+  #[error("Unexpected end of the label expression at column {0}, expected {1}")]
+  UnexpectedEndOfExpression(usize, String),
+  #[error("Unterminated quote starting at column {0} of the label expression")]
+  UnterminatedQuote(usize),
+  #[error("Unquoted ',' at column {0} of the label expression, quote labels containing commas")]
+  UnquotedComma(usize),
+  #[error("Unterminated regex starting at column {0} of the label expression")]
+  UnterminatedRegex(usize),
+  #[error("Invalid label pattern {0}: {1}")]
+  InvalidPattern(String, String),
+  #[error(
+    "Unsupported event {0}. Supported events are issues, pull_request, pull_request_target, issue_comment, workflow_dispatch and schedule"
+  )]
+  UnsupportedEvent(String),
+  #[error(
+    "Unknown command {0}. Possible commands are /epic add, /epic remove and /epic rename <name>"
+  )]
+  UnknownCommand(String),
+  #[error("Command {0} needs a {1}")]
+  MissingCommandArgument(String, String),
+  #[error("@{0} needs write permission on {1} to run epic commands")]
+  NoCommandPermission(String, String),
+  #[error("The plan is stale, {0}. Create a new plan")]
+  StalePlan(String),
+  #[error("Refused to update field {0} without the existing options {}, which would clear them on project items", .1.join(", "))]
+  OptionsOmitted(String, Vec<String>),
+  #[error("GitHub dropped the existing options {} of field {0}, which clears them on project items", .1.join(", "))]
+  OptionsDropped(String, Vec<String>),
+  #[error("GitHub API returned errors: {0}")]
+  GraphQl(String),
+  #[error("Could not find {0} {1}")]
+  OwnerNotFound(String, String),
+  #[error("Could not find project {1} of {0}")]
+  ProjectNotFound(String, i64),
+  #[error("The github-token has no access to project {1} of {0}: {2}")]
+  NoProjectAccess(String, i64, String),
+  #[error("Could not find field {0}. Available single select fields are: {}", .1.join(", "))]
+  FieldNotFound(String, Vec<String>),
+  #[error("Field {0} is not a single select field. Available single select fields are: {}", .1.join(", "))]
+  FieldNotSingleSelect(String, Vec<String>),
 }
@@ -0,0 +1,534 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::errors;
+use crate::models::{Issue, Label, Operator};
+use crate::pattern::Pattern;
+
+/// A boolean expression over label patterns, e.g. `(epic OR initiative) AND NOT wontfix`.
+///
+/// `NOT` binds stronger than `AND`, which binds stronger than `OR`. Keywords are case insensitive.
+/// Label names containing spaces, commas, parentheses or quotes, or that are keywords themselves,
+/// have to be quoted with `"` or `'`. See [`Pattern`] for globs and regexes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+  Label(Pattern),
+  Not(Box<Expression>),
+  And(Vec<Expression>),
+  Or(Vec<Expression>),
+}
+
+impl Expression {
+  /// Translates the flat `labels` and `label-operator` inputs into an expression over the
+  /// configured labels: `AND` requires all of them on the issue, `OR` any of them and `NOT` none
+  /// of them. Without any configured labels no issue matches, whatever the operator.
+  pub fn from_labels(labels: Vec<Pattern>, operator: &Operator) -> Self {
+    let labels = labels
+      .into_iter()
+      .map(Expression::Label)
+      .collect::<Vec<_>>();
+
+    if labels.is_empty() {
+      return Expression::Or(vec![]);
+    }
+
+    match operator {
+      Operator::And => Expression::And(labels),
+      Operator::Or => Expression::Or(labels),
+      Operator::Not => Expression::Not(Box::new(Expression::Or(labels))),
+    }
+  }
+
+  /// Whether nothing is configured, see [`Expression::from_labels`].
+  pub fn is_empty(&self) -> bool {
+    matches!(self, Expression::Or(expressions) if expressions.is_empty())
+  }
+
+  /// Whether the labels of `issue` satisfy the expression.
+  pub fn matches(&self, issue: &Issue) -> bool {
+    self.evaluate(&issue.labels().map(Label::name).collect::<Vec<_>>())
+  }
+
+  pub fn evaluate(&self, labels: &[&str]) -> bool {
+    match self {
+      Expression::Label(pattern) => labels.iter().any(|l| pattern.matches(l)),
+      Expression::Not(expression) => !expression.evaluate(labels),
+      Expression::And(expressions) => expressions.iter().all(|e| e.evaluate(labels)),
+      Expression::Or(expressions) => expressions.iter().any(|e| e.evaluate(labels)),
+    }
+  }
+
+  fn precedence(&self) -> u8 {
+    match self {
+      Expression::Or(expressions) | Expression::And(expressions) if expressions.len() == 1 => {
+        expressions[0].precedence()
+      }
+      Expression::Or(_) => 0,
+      Expression::And(_) => 1,
+      Expression::Not(_) | Expression::Label(_) => 2,
+    }
+  }
+
+  fn fmt_operand(&self, f: &mut std::fmt::Formatter<'_>, precedence: u8) -> std::fmt::Result {
+    if self.precedence() < precedence {
+      write!(f, "({self})")
+    } else {
+      write!(f, "{self}")
+    }
+  }
+}
+
+impl Display for Expression {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Expression::Label(pattern) if !pattern.is_regex() && needs_quotes(pattern.source()) => {
+        write!(
+          f,
+          "\"{}\"",
+          pattern.source().replace('\\', "\\\\").replace('"', "\\\"")
+        )
+      }
+      Expression::Label(pattern) => write!(f, "{pattern}"),
+      Expression::Not(expression) => {
+        write!(f, "NOT ")?;
+        expression.fmt_operand(f, 2)
+      }
+      Expression::And(expressions) | Expression::Or(expressions) => {
+        let (separator, precedence) = match self {
+          Expression::And(_) => (" AND ", 2),
+          _ => (" OR ", 1),
+        };
+
+        for (i, expression) in expressions.iter().enumerate() {
+          if i > 0 {
+            write!(f, "{separator}")?;
+          }
+          expression.fmt_operand(f, precedence)?;
+        }
+
+        Ok(())
+      }
+    }
+  }
+}
+
+impl FromStr for Expression {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let tokens = tokenize(s)?;
+    let mut parser = Parser {
+      tokens: &tokens,
+      position: 0,
+      end: s.chars().count() + 1,
+    };
+
+    let expression = parser.or()?;
+
+    match parser.peek() {
+      None => Ok(expression),
+      Some((column, token)) => Err(
+        errors::Errors::UnexpectedToken(*column, token.to_string(), "AND or OR".to_string()).into(),
+      ),
+    }
+  }
+}
+
+fn needs_quotes(label: &str) -> bool {
+  label.is_empty()
+    || Keyword::from_word(label).is_some()
+    || label
+      .chars()
+      .any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '"' | '\'' | ','))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keyword {
+  And,
+  Or,
+  Not,
+}
+
+impl Keyword {
+  fn from_word(word: &str) -> Option<Self> {
+    match word.to_uppercase().as_str() {
+      "AND" => Some(Self::And),
+      "OR" => Some(Self::Or),
+      "NOT" => Some(Self::Not),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+  Open,
+  Close,
+  Keyword(Keyword),
+  Label(String),
+}
+
+impl Display for Token {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Token::Open => write!(f, "'('"),
+      Token::Close => write!(f, "')'"),
+      Token::Keyword(Keyword::And) => write!(f, "AND"),
+      Token::Keyword(Keyword::Or) => write!(f, "OR"),
+      Token::Keyword(Keyword::Not) => write!(f, "NOT"),
+      Token::Label(label) => write!(f, "label {label}"),
+    }
+  }
+}
+
+/// Splits the expression into tokens, each with the column it starts at, counting from 1.
+fn tokenize(s: &str) -> anyhow::Result<Vec<(usize, Token)>, anyhow::Error> {
+  let mut tokens = vec![];
+  let mut chars = s.chars().enumerate().peekable();
+
+  while let Some((i, c)) = chars.next() {
+    let column = i + 1;
+
+    match c {
+      c if c.is_whitespace() => {}
+      '(' => tokens.push((column, Token::Open)),
+      ')' => tokens.push((column, Token::Close)),
+      // Commas separate labels in the labels input, so a label containing one has to be quoted.
+      ',' => return Err(errors::Errors::UnquotedComma(column).into()),
+      '"' | '\'' => {
+        let mut label = String::new();
+        loop {
+          match chars.next() {
+            Some((_, '\\')) => match chars.next() {
+              Some((_, escaped)) => label.push(escaped),
+              None => return Err(errors::Errors::UnterminatedQuote(column).into()),
+            },
+            Some((_, q)) if q == c => break,
+            Some((_, other)) => label.push(other),
+            None => return Err(errors::Errors::UnterminatedQuote(column).into()),
+          }
+        }
+        tokens.push((column, Token::Label(label)));
+      }
+      '/' => {
+        let mut regex = String::from(c);
+        loop {
+          match chars.next() {
+            Some((_, '\\')) => {
+              regex.push('\\');
+              if let Some((_, escaped)) = chars.next() {
+                regex.push(escaped);
+              }
+            }
+            Some((_, '/')) => break,
+            Some((_, other)) => regex.push(other),
+            None => return Err(errors::Errors::UnterminatedRegex(column).into()),
+          }
+        }
+        regex.push('/');
+        while let Some((_, flag)) = chars.next_if(|(_, f)| f.is_ascii_alphabetic()) {
+          regex.push(flag);
+        }
+        tokens.push((column, Token::Label(regex)));
+      }
+      c => {
+        let mut word = String::from(c);
+        while let Some((_, next)) = chars.peek() {
+          if next.is_whitespace() || matches!(next, '(' | ')' | '"' | '\'' | ',') {
+            break;
+          }
+          word.push(*next);
+          chars.next();
+        }
+
+        tokens.push((
+          column,
+          Keyword::from_word(&word)
+            .map(Token::Keyword)
+            .unwrap_or(Token::Label(word)),
+        ));
+      }
+    }
+  }
+
+  Ok(tokens)
+}
+
+struct Parser<'a> {
+  tokens: &'a [(usize, Token)],
+  position: usize,
+  end: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn peek(&self) -> Option<&'a (usize, Token)> {
+    self.tokens.get(self.position)
+  }
+
+  fn next_if(&mut self, token: &Token) -> bool {
+    let matches = self.peek().map(|(_, t)| t == token).unwrap_or_default();
+    if matches {
+      self.position += 1;
+    }
+    matches
+  }
+
+  fn or(&mut self) -> anyhow::Result<Expression, anyhow::Error> {
+    let mut expressions = vec![self.and()?];
+    while self.next_if(&Token::Keyword(Keyword::Or)) {
+      expressions.push(self.and()?);
+    }
+
+    Ok(match expressions.len() {
+      1 => expressions.remove(0),
+      _ => Expression::Or(expressions),
+    })
+  }
+
+  fn and(&mut self) -> anyhow::Result<Expression, anyhow::Error> {
+    let mut expressions = vec![self.unary()?];
+    while self.next_if(&Token::Keyword(Keyword::And)) {
+      expressions.push(self.unary()?);
+    }
+
+    Ok(match expressions.len() {
+      1 => expressions.remove(0),
+      _ => Expression::And(expressions),
+    })
+  }
+
+  fn unary(&mut self) -> anyhow::Result<Expression, anyhow::Error> {
+    if self.next_if(&Token::Keyword(Keyword::Not)) {
+      return Ok(Expression::Not(Box::new(self.unary()?)));
+    }
+
+    self.primary()
+  }
+
+  fn primary(&mut self) -> anyhow::Result<Expression, anyhow::Error> {
+    const EXPECTED: &str = "a label, NOT or '('";
+
+    let Some((column, token)) = self.peek() else {
+      return Err(errors::Errors::UnexpectedEndOfExpression(self.end, EXPECTED.to_string()).into());
+    };
+    self.position += 1;
+
+    match token {
+      Token::Label(label) => Ok(Expression::Label(Pattern::from_str(label)?)),
+      Token::Open => {
+        let expression = self.or()?;
+        match self.peek() {
+          Some((_, Token::Close)) => {
+            self.position += 1;
+            Ok(expression)
+          }
+          Some((column, token)) => Err(
+            errors::Errors::UnexpectedToken(*column, token.to_string(), "')'".to_string()).into(),
+          ),
+          None => {
+            Err(errors::Errors::UnexpectedEndOfExpression(self.end, "')'".to_string()).into())
+          }
+        }
+      }
+      token => Err(
+        errors::Errors::UnexpectedToken(*column, token.to_string(), EXPECTED.to_string()).into(),
+      ),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::str::FromStr;
+
+  use crate::errors::Errors;
+  use crate::fixtures::issue;
+  use crate::models::Operator;
+  use crate::pattern::Pattern;
+
+  use super::Expression;
+
+  fn label(name: &str) -> Expression {
+    Expression::Label(Pattern::from_str(name).unwrap())
+  }
+
+  fn patterns(labels: &[&str]) -> Vec<Pattern> {
+    labels
+      .iter()
+      .map(|l| Pattern::from_str(l).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn parse_precedence() -> anyhow::Result<(), anyhow::Error> {
+    assert_eq!(
+      Expression::from_str("epic OR initiative AND NOT wontfix")?,
+      Expression::Or(vec![
+        label("epic"),
+        Expression::And(vec![
+          label("initiative"),
+          Expression::Not(Box::new(label("wontfix")))
+        ])
+      ])
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn parse_parentheses_and_quotes() -> anyhow::Result<(), anyhow::Error> {
+    let expression =
+      Expression::from_str(r#"(epic or 'good, first') and not "won't fix" AND NOT duplicate"#)?;
+
+    assert_eq!(
+      expression,
+      Expression::And(vec![
+        Expression::Or(vec![label("epic"), label("good, first")]),
+        Expression::Not(Box::new(label("won't fix"))),
+        Expression::Not(Box::new(label("duplicate"))),
+      ])
+    );
+    assert_eq!(
+      expression.to_string(),
+      r#"(epic OR "good, first") AND NOT "won't fix" AND NOT duplicate"#
+    );
+    assert_eq!(Expression::from_str(&expression.to_string())?, expression);
+
+    let expression = Expression::from_str("'a,b' OR /a,b/")?;
+    assert_eq!(expression.to_string(), "\"a,b\" OR /a,b/");
+    assert_eq!(Expression::from_str(&expression.to_string())?, expression);
+
+    Ok(())
+  }
+
+  #[test]
+  fn evaluate_expression() -> anyhow::Result<(), anyhow::Error> {
+    let expression =
+      Expression::from_str("(epic OR initiative) AND NOT wontfix AND NOT duplicate")?;
+
+    assert!(expression.evaluate(&["epic"]));
+    assert!(expression.evaluate(&["initiative", "bug"]));
+    assert!(!expression.evaluate(&["epic", "wontfix"]));
+    assert!(!expression.evaluate(&["initiative", "duplicate"]));
+    assert!(!expression.evaluate(&[]));
+
+    Ok(())
+  }
+
+  #[test]
+  fn translate_labels_and_operator() {
+    let labels = patterns(&["epic", "initiative"]);
+
+    assert_eq!(
+      Expression::from_labels(labels.clone(), &Operator::Or).to_string(),
+      "epic OR initiative"
+    );
+    assert_eq!(
+      Expression::from_labels(labels.clone(), &Operator::And).to_string(),
+      "epic AND initiative"
+    );
+    assert_eq!(
+      Expression::from_labels(labels, &Operator::Not).to_string(),
+      "NOT (epic OR initiative)"
+    );
+  }
+
+  #[test]
+  fn operator_truth_table() {
+    let configured = patterns(&["epic", "initiative"]);
+    let issues = [
+      ("empty", issue(&[])),
+      ("subset", issue(&["epic"])),
+      ("equal", issue(&["initiative", "epic"])),
+      ("superset", issue(&["epic", "initiative", "bug"])),
+      ("disjoint", issue(&["bug", "wontfix"])),
+    ];
+    let table = [
+      (Operator::And, [false, false, true, true, false]),
+      (Operator::Or, [false, true, true, true, false]),
+      (Operator::Not, [true, false, false, false, true]),
+    ];
+
+    for (operator, expected) in table {
+      let expression = Expression::from_labels(configured.clone(), &operator);
+      for ((case, issue), expected) in issues.iter().zip(expected) {
+        assert_eq!(
+          expression.matches(issue),
+          expected,
+          "{operator} with {case} issue labels"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn no_configured_labels_match_nothing() {
+    for operator in [Operator::And, Operator::Or, Operator::Not] {
+      let expression = Expression::from_labels(vec![], &operator);
+      for labels in [&[][..], &["epic"], &["epic", "bug"]] {
+        assert!(
+          !expression.matches(&issue(labels)),
+          "{operator} with {labels:?}"
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn parse_patterns() -> anyhow::Result<(), anyhow::Error> {
+    let expression = Expression::from_str("epic:* OR /^(epic|initiative)$/i AND NOT 'won t fix'")?;
+
+    assert!(expression.evaluate(&["epic:roadmap"]));
+    assert!(expression.evaluate(&["Initiative"]));
+    assert!(!expression.evaluate(&["Initiative", "won t fix"]));
+    assert!(!expression.evaluate(&["type/epic"]));
+    assert_eq!(
+      expression.to_string(),
+      "epic:* OR /^(epic|initiative)$/i AND NOT \"won t fix\""
+    );
+    assert_eq!(Expression::from_str(&expression.to_string())?, expression);
+
+    Ok(())
+  }
+
+  #[test]
+  fn parse_errors() {
+    let error = |s: &str| {
+      Expression::from_str(s)
+        .unwrap_err()
+        .downcast::<Errors>()
+        .unwrap()
+    };
+
+    assert!(matches!(
+      error("epic AND"),
+      Errors::UnexpectedEndOfExpression(9, _)
+    ));
+    assert!(matches!(
+      error("(epic OR initiative"),
+      Errors::UnexpectedEndOfExpression(20, _)
+    ));
+    assert!(matches!(
+      error("epic \"won't fix"),
+      Errors::UnterminatedQuote(6)
+    ));
+    assert!(matches!(
+      error("epic initiative"),
+      Errors::UnexpectedToken(6, _, _)
+    ));
+    assert!(matches!(
+      error("epic AND OR wontfix"),
+      Errors::UnexpectedToken(10, _, _)
+    ));
+    assert!(matches!(
+      error("epic OR /^epic"),
+      Errors::UnterminatedRegex(9)
+    ));
+    assert!(matches!(error("/^epic(/"), Errors::InvalidPattern(_, _)));
+    assert!(matches!(error("epic OR a,b"), Errors::UnquotedComma(10)));
+    assert!(matches!(error(",epic"), Errors::UnquotedComma(1)));
+    assert_eq!(
+      error("epic )").to_string(),
+      "Unexpected ')' at column 6 of the label expression, expected AND or OR"
+    );
+  }
+}
@@ -0,0 +1,151 @@
+//! A fake GitHub API for tests, answering every request with the next of a list of canned
+//! responses.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::queries::Client;
+
+/// A request the fake server received.
+#[derive(Debug, Clone)]
+pub struct Request {
+  pub path: String,
+  pub body: serde_json::Value,
+}
+
+pub struct FakeGitHub {
+  url: String,
+  requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl FakeGitHub {
+  pub async fn start(responses: Vec<serde_json::Value>) -> anyhow::Result<Self, anyhow::Error> {
+    Self::start_with_status(responses.into_iter().map(|r| (200, r)).collect()).await
+  }
+
+  /// Starts a server answering with the status codes and bodies.
+  pub async fn start_with_status(
+    responses: Vec<(u16, serde_json::Value)>,
+  ) -> anyhow::Result<Self, anyhow::Error> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/", listener.local_addr()?);
+    let requests = Arc::new(Mutex::new(vec![]));
+
+    tokio::spawn(serve(listener, responses, requests.clone()));
+
+    Ok(Self { url, requests })
+  }
+
+  pub fn client(&self) -> anyhow::Result<Client, anyhow::Error> {
+    Ok(Client::new(
+      octocrab::OctocrabBuilder::new()
+        .base_url(self.url.as_str())?
+        .build()?,
+      false,
+    ))
+  }
+
+  /// A client that sends queries to the fake server, but only records mutations.
+  pub fn dry_run_client(&self) -> anyhow::Result<Client, anyhow::Error> {
+    Ok(Client::new(
+      octocrab::OctocrabBuilder::new()
+        .base_url(self.url.as_str())?
+        .build()?,
+      true,
+    ))
+  }
+
+  /// The JSON bodies of all requests so far, `null` for requests without one.
+  pub fn requests(&self) -> Vec<serde_json::Value> {
+    self
+      .received()
+      .into_iter()
+      .map(|request| request.body)
+      .collect()
+  }
+
+  /// The paths, with query, of all requests so far.
+  pub fn paths(&self) -> Vec<String> {
+    self
+      .received()
+      .into_iter()
+      .map(|request| request.path)
+      .collect()
+  }
+
+  fn received(&self) -> Vec<Request> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+async fn serve(
+  listener: TcpListener,
+  responses: Vec<(u16, serde_json::Value)>,
+  requests: Arc<Mutex<Vec<Request>>>,
+) {
+  for (status, response) in responses {
+    let Ok((mut stream, _)) = listener.accept().await else {
+      return;
+    };
+    let Ok(request) = read_request(&mut stream).await else {
+      return;
+    };
+    requests.lock().unwrap().push(request);
+
+    let response = response.to_string();
+    let _ = stream
+      .write_all(
+        format!(
+          "HTTP/1.1 {status} Fake\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{response}",
+          response.len()
+        )
+        .as_bytes(),
+      )
+      .await;
+  }
+}
+
+async fn read_request(stream: &mut TcpStream) -> anyhow::Result<Request, anyhow::Error> {
+  let mut request = vec![];
+  let mut buffer = [0; 4096];
+
+  let header_end = loop {
+    let read = stream.read(&mut buffer).await?;
+    anyhow::ensure!(
+      read > 0,
+      "Connection closed before the request was complete"
+    );
+    request.extend_from_slice(&buffer[..read]);
+
+    if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+      break end + 4;
+    }
+  };
+
+  let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+  let path = head
+    .split_whitespace()
+    .nth(1)
+    .unwrap_or_default()
+    .to_string();
+  let content_length = head
+    .lines()
+    .filter_map(|line| line.split_once(':'))
+    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+    .and_then(|(_, value)| value.trim().parse().ok())
+    .unwrap_or(0);
+
+  while request.len() < header_end + content_length {
+    let read = stream.read(&mut buffer).await?;
+    anyhow::ensure!(read > 0, "Connection closed before the body was complete");
+    request.extend_from_slice(&buffer[..read]);
+  }
+
+  Ok(Request {
+    path,
+    body: serde_json::from_slice(&request[header_end..header_end + content_length])
+      .unwrap_or_default(),
+  })
+}
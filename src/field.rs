@@ -0,0 +1,170 @@
+use crate::errors;
+use crate::models::{ProjectId, SingleSelectField, SingleSelectOption};
+use crate::queries::{
+  self,
+  get_project_fields::{
+    self, GetProjectFieldsNode, GetProjectFieldsNodeOnProjectV2FieldsNodes as FieldNode,
+  },
+  Client, GetProjectFields,
+};
+
+#[derive(Debug)]
+enum Field {
+  SingleSelect(SingleSelectField),
+  Other(String),
+}
+
+impl Field {
+  fn name(&self) -> &str {
+    match self {
+      Field::SingleSelect(field) => field.name(),
+      Field::Other(name) => name,
+    }
+  }
+}
+
+impl Field {
+  fn new(project_id: &ProjectId, node: FieldNode) -> Self {
+    match node {
+      FieldNode::ProjectV2SingleSelectField(field) => Field::SingleSelect(SingleSelectField::new(
+        project_id.clone(),
+        field.id,
+        field.name,
+        field
+          .options
+          .into_iter()
+          .map(|o| SingleSelectOption::new(Some(o.id), o.name, o.color, o.description))
+          .collect(),
+      )),
+      FieldNode::ProjectV2Field(field) => Field::Other(field.name),
+      FieldNode::ProjectV2IterationField(field) => Field::Other(field.name),
+    }
+  }
+}
+
+/// Pages through the fields of the project and returns the single select field with the given
+/// name.
+pub async fn find(
+  client: &Client,
+  project_id: &ProjectId,
+  field_name: &str,
+) -> anyhow::Result<SingleSelectField, anyhow::Error> {
+  let mut fields = vec![];
+  let mut cursor = None;
+
+  loop {
+    let data = queries::post::<GetProjectFields>(
+      client,
+      get_project_fields::Variables {
+        project_id: project_id.to_string(),
+        cursor,
+      },
+    )
+    .await?;
+
+    let page = match data.node {
+      Some(GetProjectFieldsNode::ProjectV2(project)) => project.fields,
+      _ => anyhow::bail!("Node {project_id} is not a project"),
+    };
+
+    fields.extend(
+      page
+        .nodes
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|node| Field::new(project_id, node)),
+    );
+
+    match page.page_info.end_cursor {
+      Some(end_cursor) if page.page_info.has_next_page => cursor = Some(end_cursor),
+      _ => break,
+    }
+  }
+
+  select(field_name, fields)
+}
+
+fn select(
+  field_name: &str,
+  fields: Vec<Field>,
+) -> anyhow::Result<SingleSelectField, anyhow::Error> {
+  let available = fields
+    .iter()
+    .filter_map(|f| match f {
+      Field::SingleSelect(field) => Some(field.name().to_string()),
+      Field::Other(_) => None,
+    })
+    .collect::<Vec<_>>();
+
+  match fields.into_iter().find(|f| f.name() == field_name) {
+    Some(Field::SingleSelect(field)) => Ok(field),
+    Some(Field::Other(_)) => {
+      Err(errors::Errors::FieldNotSingleSelect(field_name.to_string(), available).into())
+    }
+    None => Err(errors::Errors::FieldNotFound(field_name.to_string(), available).into()),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::errors::Errors;
+  use crate::fixtures::project_id;
+  use crate::models::{SingleSelectField, SingleSelectOption};
+
+  use super::{select, Field};
+
+  fn fields() -> Vec<Field> {
+    vec![
+      Field::Other("Title".to_string()),
+      Field::SingleSelect(SingleSelectField::new(
+        project_id(),
+        "PVTSSF_status".to_string(),
+        "Status".to_string(),
+        vec![],
+      )),
+      Field::SingleSelect(SingleSelectField::new(
+        project_id(),
+        "PVTSSF_epic".to_string(),
+        "Epic".to_string(),
+        vec![SingleSelectOption::new(
+          Some("1".to_string()),
+          "Test19".to_string(),
+          Default::default(),
+          String::new(),
+        )],
+      )),
+    ]
+  }
+
+  #[test]
+  fn select_single_select_field() -> anyhow::Result<(), anyhow::Error> {
+    let field = select("Epic", fields())?;
+
+    assert_eq!(field.id(), "PVTSSF_epic");
+    assert_eq!(field.options().len(), 1);
+
+    Ok(())
+  }
+
+  #[test]
+  fn select_field_of_wrong_type() {
+    let err = select("Title", fields()).unwrap_err();
+
+    assert!(matches!(
+      err.downcast_ref::<Errors>(),
+      Some(Errors::FieldNotSingleSelect(name, available))
+        if name == "Title" && available == &vec!["Status".to_string(), "Epic".to_string()]
+    ));
+  }
+
+  #[test]
+  fn select_missing_field() {
+    let err = select("Epics", fields()).unwrap_err();
+
+    assert_eq!(
+      err.to_string(),
+      "Could not find field Epics. Available single select fields are: Status, Epic"
+    );
+  }
+}
@@ -0,0 +1,84 @@
+//! Factories for the params, issues, options and fields of tests.
+
+use crate::models::{Args, Color, Issue, Params, ProjectId, SingleSelectField, SingleSelectOption};
+
+pub const PROJECT_URL: &str = "https://github.com/orgs/JenSeReal/projects/3";
+/// The repository of all issues.
+pub const REPOSITORY: &str = "JenSeReal/example-add-epic-issue-to-project";
+
+/// Parses the inputs after `project-url` and `github-token`, starting with `labels`.
+pub fn try_params(inputs: &[&str]) -> anyhow::Result<Params, anyhow::Error> {
+  let mut args = vec!["add-epic-to-project", PROJECT_URL, "token"];
+  args.extend_from_slice(inputs);
+
+  Params::try_from(Args(args.into_iter().map(String::from).collect()))
+}
+
+pub fn params(inputs: &[&str]) -> Params {
+  try_params(inputs).unwrap()
+}
+
+/// An issue of the REST API as JSON, e.g. to embed it in an event.
+pub fn issue_json(
+  id: u64,
+  number: u32,
+  title: &str,
+  labels: &[&str],
+  state: &str,
+) -> serde_json::Value {
+  serde_json::json!({
+    "id": id,
+    "node_id": format!("I_{id}"),
+    "labels": labels
+      .iter()
+      .enumerate()
+      .map(|(id, name)| serde_json::json!({ "id": id, "name": name }))
+      .collect::<Vec<_>>(),
+    "number": number,
+    "title": title,
+    "state": state,
+    "html_url": format!("https://github.com/{REPOSITORY}/issues/{number}")
+  })
+}
+
+pub fn issue_with(id: u64, number: u32, title: &str, labels: &[&str], state: &str) -> Issue {
+  serde_json::from_value(issue_json(id, number, title, labels, state)).unwrap()
+}
+
+/// The open issue #19 "Test19" with the labels.
+pub fn issue(labels: &[&str]) -> Issue {
+  issue_with(1611401201, 19, "Test19", labels, "open")
+}
+
+/// An option, with the marker of the issue of [`REPOSITORY`] with the id and number if there is
+/// one.
+pub fn option(
+  id: Option<&str>,
+  name: &str,
+  color: Color,
+  issue: Option<(u64, u32)>,
+) -> SingleSelectOption {
+  SingleSelectOption::new(
+    id.map(String::from),
+    name.to_string(),
+    color,
+    issue
+      .map(|(id, number)| format!("{REPOSITORY}#{number} issue-id:{id}"))
+      .unwrap_or_default(),
+  )
+}
+
+/// The id of the project of [`PROJECT_URL`].
+pub fn project_id() -> ProjectId {
+  ProjectId::new("PVT_kwDOA".to_string())
+}
+
+/// The single select field "Epic" of the project with the options.
+pub fn field(options: Vec<SingleSelectOption>) -> SingleSelectField {
+  SingleSelectField::new(
+    project_id(),
+    "PVTSSF_epic".to_string(),
+    "Epic".to_string(),
+    options,
+  )
+}
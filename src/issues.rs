@@ -0,0 +1,119 @@
+use serde::Serialize;
+
+use crate::models::{Issue, StateFilter};
+use crate::queries::{self, Client};
+
+const PER_PAGE: u32 = 100;
+
+#[derive(Serialize)]
+struct ListPullRequests {
+  state: &'static str,
+  sort: &'static str,
+  direction: &'static str,
+  per_page: u32,
+  page: u32,
+}
+
+#[derive(Serialize)]
+struct ListIssues {
+  state: String,
+  sort: &'static str,
+  direction: &'static str,
+  per_page: u32,
+  page: u32,
+}
+
+/// Lists one page of issues of the repository, oldest first. Like the REST API this includes pull
+/// requests, so only an empty page marks the end.
+pub async fn list(
+  client: &Client,
+  repository: &str,
+  state: &StateFilter,
+  page: u32,
+) -> anyhow::Result<Vec<Issue>, anyhow::Error> {
+  queries::get(
+    client,
+    &format!("repos/{repository}/issues"),
+    &ListIssues {
+      state: state.to_string(),
+      sort: "created",
+      direction: "asc",
+      per_page: PER_PAGE,
+      page,
+    },
+  )
+  .await
+}
+
+/// Lists all issues of the repository without pull requests, oldest first.
+pub async fn list_all(
+  client: &Client,
+  repository: &str,
+  state: &StateFilter,
+) -> anyhow::Result<Vec<Issue>, anyhow::Error> {
+  let mut issues = vec![];
+
+  for page in 1.. {
+    let listed = list(client, repository, state, page).await?;
+    if listed.is_empty() {
+      break;
+    }
+
+    issues.extend(listed.into_iter().filter(|i| !i.is_pull_request()));
+  }
+
+  Ok(issues)
+}
+
+/// Lists all pull requests of the repository, oldest first. Unlike in the issues API, they have
+/// the id of the pull request, which the options of pull request epics are created with.
+pub async fn list_pull_requests(
+  client: &Client,
+  repository: &str,
+) -> anyhow::Result<Vec<Issue>, anyhow::Error> {
+  let mut pull_requests = vec![];
+
+  for page in 1.. {
+    let listed: Vec<Issue> = queries::get(
+      client,
+      &format!("repos/{repository}/pulls"),
+      &ListPullRequests {
+        state: "all",
+        sort: "created",
+        direction: "asc",
+        per_page: PER_PAGE,
+        page,
+      },
+    )
+    .await?;
+    if listed.is_empty() {
+      break;
+    }
+
+    pull_requests.extend(listed);
+  }
+
+  Ok(pull_requests)
+}
+
+/// Gets the pull request with the number. Comments on pull requests come with the pull request as
+/// an issue, which has a different id than the pull request itself.
+pub async fn get_pull_request(
+  client: &Client,
+  repository: &str,
+  number: u32,
+) -> anyhow::Result<Issue, anyhow::Error> {
+  queries::get(client, &format!("repos/{repository}/pulls/{number}"), &()).await
+}
+
+/// Whether the issue was deleted. GitHub answers 410 for deleted issues, and 404 both for issues
+/// that never existed and ones the token can't see, so only 410 counts.
+pub async fn is_deleted(
+  client: &Client,
+  repository: &str,
+  number: u32,
+) -> anyhow::Result<bool, anyhow::Error> {
+  let status = queries::status(client, &format!("repos/{repository}/issues/{number}")).await?;
+
+  Ok(status == 410)
+}
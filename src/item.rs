@@ -0,0 +1,275 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Issue, ProjectId, SingleSelectField, SingleSelectOption};
+use crate::queries::{
+  self, add_project_item,
+  get_project_items::{
+    self, GetProjectItemsNode, GetProjectItemsNodeOnProjectV2ItemsNodesContent as Content,
+    GetProjectItemsNodeOnProjectV2ItemsNodesFieldValueByName as Value,
+  },
+  set_single_select_value, AddProjectItem, Client, GetProjectItems, SetSingleSelectValue,
+};
+
+/// Adds the issue to the project and sets its value of the field to the given option. Adding an
+/// issue that already is an item of the project returns the existing item. In a dry run the item
+/// id, and the id of an option that would have been created, are placeholders.
+pub async fn add_with_value(
+  client: &Client,
+  project_id: &ProjectId,
+  field: &SingleSelectField,
+  issue: &Issue,
+  option: &SingleSelectOption,
+) -> anyhow::Result<String, anyhow::Error> {
+  let option_id = match option.id() {
+    Some(id) => id.to_string(),
+    None if client.dry_run() => format!("<option of issue #{}>", issue.number()),
+    None => anyhow::bail!("Option \"{}\" has no id yet", option.name()),
+  };
+
+  let data = queries::mutate::<AddProjectItem>(
+    client,
+    add_project_item::Variables {
+      project_id: project_id.to_string(),
+      content_id: issue.node_id().to_string(),
+    },
+  )
+  .await?;
+
+  let item_id = match data {
+    Some(data) => data
+      .add_project_v2_item_by_id
+      .and_then(|payload| payload.item)
+      .map(|item| item.id)
+      .context(format!(
+        "Could not add issue #{} to project {project_id}",
+        issue.number()
+      ))?,
+    None => format!("<item of issue #{}>", issue.number()),
+  };
+
+  set_value(client, field, &item_id, &option_id).await?;
+
+  Ok(item_id)
+}
+
+/// Sets the value of the field of the item to the option.
+pub async fn set_value(
+  client: &Client,
+  field: &SingleSelectField,
+  item_id: &str,
+  option_id: &str,
+) -> anyhow::Result<(), anyhow::Error> {
+  queries::mutate::<SetSingleSelectValue>(
+    client,
+    set_single_select_value::Variables {
+      project_id: field.project_id().to_string(),
+      item_id: item_id.to_string(),
+      field_id: field.id().to_string(),
+      option_id: option_id.to_string(),
+    },
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// An item of the project with a value of the field.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProjectItem {
+  id: String,
+  title: String,
+  url: Option<String>,
+  /// The database id of the issue or pull request of the item, the id options are created with.
+  content_id: Option<u64>,
+  option_id: String,
+  option_name: String,
+}
+
+impl ProjectItem {
+  pub fn new(
+    id: String,
+    title: String,
+    url: Option<String>,
+    content_id: Option<u64>,
+    option_id: String,
+    option_name: String,
+  ) -> Self {
+    Self {
+      id,
+      title,
+      url,
+      content_id,
+      option_id,
+      option_name,
+    }
+  }
+
+  pub fn id(&self) -> &str {
+    &self.id
+  }
+
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+
+  pub fn url(&self) -> Option<&str> {
+    self.url.as_deref()
+  }
+
+  pub fn content_id(&self) -> Option<u64> {
+    self.content_id
+  }
+
+  pub fn option_id(&self) -> &str {
+    &self.option_id
+  }
+
+  pub fn option_name(&self) -> &str {
+    &self.option_name
+  }
+}
+
+/// Pages through the items of the project and returns those with a value of the field.
+pub async fn list_with_value(
+  client: &Client,
+  project_id: &ProjectId,
+  field: &SingleSelectField,
+) -> anyhow::Result<Vec<ProjectItem>, anyhow::Error> {
+  let mut items = vec![];
+  let mut cursor = None;
+
+  loop {
+    let data = queries::post::<GetProjectItems>(
+      client,
+      get_project_items::Variables {
+        project_id: project_id.to_string(),
+        field_name: field.name().to_string(),
+        cursor,
+      },
+    )
+    .await?;
+
+    let page = match data.node {
+      Some(GetProjectItemsNode::ProjectV2(project)) => project.items,
+      _ => anyhow::bail!("Node {project_id} is not a project"),
+    };
+
+    items.extend(
+      page
+        .nodes
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|node| {
+          let Some(Value::ProjectV2ItemFieldSingleSelectValue(value)) = node.field_value_by_name
+          else {
+            return None;
+          };
+          let (title, url, content_id) = match node.content {
+            Some(Content::Issue(issue)) => (issue.title, Some(issue.url), issue.database_id),
+            Some(Content::PullRequest(pull_request)) => (
+              pull_request.title,
+              Some(pull_request.url),
+              pull_request.database_id,
+            ),
+            Some(Content::DraftIssue(draft)) => (draft.title, None, None),
+            None => (String::new(), None, None),
+          };
+
+          Some(ProjectItem::new(
+            node.id,
+            title,
+            url,
+            content_id.and_then(|id| u64::try_from(id).ok()),
+            value.option_id.unwrap_or_default(),
+            value.name.unwrap_or_default(),
+          ))
+        }),
+    );
+
+    match page.page_info.end_cursor {
+      Some(end_cursor) if page.page_info.has_next_page => cursor = Some(end_cursor),
+      _ => break,
+    }
+  }
+
+  Ok(items)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::fake_github::FakeGitHub;
+  use crate::fixtures::{field, issue};
+  use crate::models::{Issue, ProjectId, SingleSelectOption};
+  use crate::queries::Client;
+
+  fn epic() -> Issue {
+    issue(&["epic"])
+  }
+
+  #[tokio::test]
+  async fn add_item_and_set_value() -> anyhow::Result<(), anyhow::Error> {
+    let github = FakeGitHub::start(vec![
+      serde_json::json!({ "data": { "addProjectV2ItemById": { "item": { "id": "PVTI_19" } } } }),
+      serde_json::json!({
+        "data": { "updateProjectV2ItemFieldValue": { "projectV2Item": { "id": "PVTI_19" } } }
+      }),
+    ])
+    .await?;
+    let option = SingleSelectOption::new(
+      Some("a1".to_string()),
+      "Test19".to_string(),
+      Default::default(),
+      "#19 issue-id:1611401201".to_string(),
+    );
+
+    let item_id = super::add_with_value(
+      &github.client()?,
+      &ProjectId::new("PVT_kwDOA".to_string()),
+      &field(vec![]),
+      &epic(),
+      &option,
+    )
+    .await?;
+
+    assert_eq!(item_id, "PVTI_19");
+    let requests = github.requests();
+    assert_eq!(
+      requests.iter().map(|r| &r["variables"]).collect::<Vec<_>>(),
+      [
+        &serde_json::json!({ "projectId": "PVT_kwDOA", "contentId": "I_1611401201" }),
+        &serde_json::json!({
+          "projectId": "PVT_kwDOA",
+          "itemId": "PVTI_19",
+          "fieldId": "PVTSSF_epic",
+          "optionId": "a1"
+        }),
+      ]
+    );
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn plan_item_with_placeholders_in_dry_run() -> anyhow::Result<(), anyhow::Error> {
+    let client = Client::new(octocrab::Octocrab::default(), true);
+
+    let item_id = super::add_with_value(
+      &client,
+      &ProjectId::new("PVT_kwDOA".to_string()),
+      &field(vec![]),
+      &epic(),
+      &SingleSelectOption::for_issue(&epic(), &[]),
+    )
+    .await?;
+
+    assert_eq!(item_id, "<item of issue #19>");
+    let planned = client.planned();
+    assert_eq!(planned.len(), 2);
+    assert_eq!(planned[1]["variables"]["itemId"], "<item of issue #19>");
+    assert_eq!(planned[1]["variables"]["optionId"], "<option of issue #19>");
+
+    Ok(())
+  }
+}
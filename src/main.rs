@@ -1,36 +1,248 @@
 use std::{env, fs};
 
-use crate::models::{Args, Label, Operator, Params};
-// use std::fs::write;
-// use std::process::exit;
+use crate::errors::Errors;
+use crate::models::{
+  Action, Args, Event, IssueEvent, Operation, Params, RunMode, SingleSelectOption, Transition,
+};
+use crate::output::Outputs;
+use crate::queries::Client;
+use crate::summary::Summary;
 
+mod audit;
+mod backfill;
+mod command;
+mod criteria;
 mod errors;
+mod expression;
+#[cfg(test)]
+mod fake_github;
+mod field;
+#[cfg(test)]
+mod fixtures;
+mod issues;
+mod item;
 mod models;
+mod options;
+mod output;
+mod pattern;
+mod plan;
+mod project;
+mod queries;
+mod reconcile;
+mod repositories;
+mod summary;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<(), anyhow::Error> {
-  // let mut github_output_path = env::var("GITHUB_OUTPUT").unwrap();
-
-  let params = Params::try_from(Args(env::args().collect()))?;
-  let _crab = octocrab::OctocrabBuilder::new()
-    .personal_token(params.github_token().to_string())
-    .build()?;
+  let params = Params::try_from(Args(env::args().collect())).map_err(annotate)?;
+  let client = Client::new(
+    octocrab::OctocrabBuilder::new()
+      .personal_token(params.github_token().to_string())
+      .build()?,
+    params.dry_run(),
+  );
 
   let event_string = fs::read_to_string(env::var("GITHUB_EVENT_PATH")?)?;
-  let event: models::IssueEvent = serde_json::from_str(&event_string)?;
+  let event_name = env::var("GITHUB_EVENT_NAME").unwrap_or_else(|_| "issues".to_string());
+  let event = Event::parse(&event_name, &event_string)?;
+
+  let mut outputs = Outputs::default();
+  let mut summary = Summary::default();
+  let result = match (params.run_mode(), event) {
+    // Plan and audit only read, so they run on every event, e.g. to show the plan on a pull request.
+    (RunMode::Plan, _) => plan::plan(&params, &client, &mut outputs, &mut summary).await,
+    (RunMode::Audit, _) => audit::run(&params, &client, &mut outputs, &mut summary).await,
+    (_, Event::Issues(event)) => run(&params, &client, &event, &mut outputs, &mut summary).await,
+    (_, Event::PullRequest(event)) => {
+      run(&params, &client, &event.into(), &mut outputs, &mut summary).await
+    }
+    (_, Event::IssueComment(event)) => {
+      command::run(&params, &client, &event, &mut outputs, &mut summary).await
+    }
+    (RunMode::Backfill, Event::Scheduled) => {
+      backfill::run(&params, &client, &mut outputs, &mut summary).await
+    }
+    (RunMode::Sync, Event::Scheduled) => {
+      reconcile::run(&params, &client, &mut outputs, &mut summary).await
+    }
+    (RunMode::Apply, Event::Scheduled) => {
+      plan::apply(&params, &client, &mut outputs, &mut summary).await
+    }
+  };
+
+  if client.dry_run() {
+    let planned = client.planned();
+    outputs.set("planned-mutations", serde_json::to_string(&planned)?);
+    summary.planned(&planned)?;
+  }
+
+  outputs.write()?;
+  summary.write()?;
+
+  result
+}
+
+/// Reports every invalid input as its own annotation.
+fn annotate(error: anyhow::Error) -> anyhow::Error {
+  if let Some(Errors::InvalidInputs(inputs)) = error.downcast_ref::<Errors>() {
+    for (input, message) in inputs {
+      println!(
+        "{}",
+        output::error(&format!("Invalid input {input}"), message)
+      );
+    }
+  }
+  error
+}
+
+async fn run(
+  params: &Params,
+  client: &Client,
+  event: &IssueEvent,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  let issue = event.issue();
+
+  let transition = params.transition(event);
+  let is_epic = transition.is_epic();
+  outputs
+    .set("is-epic", is_epic)
+    .set("transition", transition);
+  summary.filter(params, issue, is_epic);
 
-  let mut labels = event.issue().labels();
-  let contains = |l: &Label| params.labels().contains(&l.name().to_string());
+  if transition == Transition::Unchanged(false) {
+    println!(
+      "Issue #{} does not match the label filter, nothing to do",
+      issue.number()
+    );
+    return Ok(());
+  }
 
-  let is_epic = match params.operator() {
-    Operator::And => labels.all(contains),
-    Operator::Or => labels.any(contains),
-    Operator::Not => !labels.any(contains),
+  // These actions never change the option, so they don't need the project or the field.
+  if matches!(
+    event.action(),
+    Action::Pinned | Action::Unpinned | Action::Locked | Action::Unlocked | Action::Other
+  ) {
+    println!(
+      "Nothing to do for {} events of issue #{}",
+      event.action(),
+      issue.number()
+    );
+    return Ok(());
+  }
+
+  let project_id = project::resolve(client, params.project()).await?;
+  outputs.set("project-id", &project_id);
+  summary.project(params, &project_id);
+
+  let field = field::find(client, &project_id, params.field_name()).await?;
+  outputs.set("field-id", field.id());
+  summary.field(&field);
+
+  let operation = match (event.action(), transition) {
+    (_, Transition::BecameEpic) => {
+      options::reopen(client, &field, issue, params.on_unlabeled()).await?
+    }
+    (_, Transition::StoppedBeingEpic) => {
+      options::close(client, &field, issue, params.on_unlabeled()).await?
+    }
+    (Action::Edited, _) => match event.changes().title() {
+      Some(change) => options::rename(client, &field, issue, change.from()).await?,
+      None => options::add(client, &field, issue).await?,
+    },
+    (Action::Closed, _) => options::close(client, &field, issue, params.on_close()).await?,
+    (Action::Reopened, _) => options::reopen(client, &field, issue, params.on_close()).await?,
+    (Action::Deleted, _) => options::remove(client, &field, issue).await?,
+    // A transferred issue gets a new id in the new repository, the option moves to it.
+    (Action::Transferred, _) => match event.changes().new_issue() {
+      Some(new_issue) => options::transfer(client, &field, issue, new_issue).await?,
+      None => Operation::Skipped(SingleSelectOption::for_issue(issue, field.options())),
+    },
+    (
+      Action::Opened
+      | Action::Labeled
+      | Action::Unlabeled
+      | Action::Assigned
+      | Action::Unassigned
+      | Action::Milestoned
+      | Action::Demilestoned
+      | Action::Typed
+      | Action::Untyped,
+      _,
+    ) => options::add(client, &field, issue).await?,
+    (Action::Pinned | Action::Unpinned | Action::Locked | Action::Unlocked | Action::Other, _) => {
+      unreachable!(
+        "{} events return before the project is resolved",
+        event.action()
+      )
+    }
   };
 
-  dbg!(event.issue().labels().collect::<Vec<_>>());
+  let option = operation.option();
+  outputs
+    .set("option-id", option.id().unwrap_or_default())
+    .set("option-name", option.name())
+    .set("operation", &operation);
+  summary.operation(&operation);
 
-  dbg!(is_epic);
+  println!(
+    "Option \"{}\" of issue #{} in field \"{}\" of project {}: {}",
+    option.name(),
+    issue.number(),
+    field.name(),
+    project_id,
+    operation
+  );
+
+  if params.add_epic_item()
+    && is_epic
+    && !matches!(operation, Operation::Removed(_) | Operation::Transferred(_))
+  {
+    let item_id = item::add_with_value(client, &project_id, &field, issue, option).await?;
+    summary.item(&item_id);
+    println!(
+      "Set \"{}\" of item {} for issue #{} to \"{}\"",
+      field.name(),
+      item_id,
+      issue.number(),
+      option.name()
+    );
+  }
 
   Ok(())
 }
+
+#[cfg(test)]
+mod test {
+  use crate::fake_github::FakeGitHub;
+  use crate::fixtures::{issue_json, params};
+  use crate::models::IssueEvent;
+  use crate::output::Outputs;
+  use crate::summary::Summary;
+
+  #[tokio::test]
+  async fn ignored_actions_make_no_request() -> anyhow::Result<(), anyhow::Error> {
+    let github = FakeGitHub::start(vec![]).await?;
+
+    for action in ["pinned", "unpinned", "locked", "unlocked", "converted"] {
+      let event: IssueEvent = serde_json::from_value(serde_json::json!({
+        "action": action,
+        "issue": issue_json(1611401201, 19, "Test19", &["epic"], "open")
+      }))?;
+
+      super::run(
+        &params(&["epic"]),
+        &github.client()?,
+        &event,
+        &mut Outputs::default(),
+        &mut Summary::default(),
+      )
+      .await?;
+    }
+
+    assert!(github.paths().is_empty());
+
+    Ok(())
+  }
+}
@@ -2,10 +2,12 @@
 
 use std::{fmt::Display, str::FromStr};
 
-use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
+use crate::criteria::{Criteria, Mode};
 use crate::errors;
+use crate::expression::Expression;
+use crate::pattern::Pattern;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OwnerType {
@@ -46,23 +48,29 @@ impl FromStr for Project {
   type Err = anyhow::Error;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid =
+      |reason: &str| errors::Errors::InvalidProjectUrl(s.to_string(), reason.to_string());
     let split = s.split('/').skip(3).map(String::from).collect::<Vec<_>>();
 
-    let owner_type = OwnerType::from_str(
-      split
-        .get(0)
-        .ok_or(anyhow::anyhow!("Could not parse owner type from {s}"))?,
-    )?;
+    let owner_type = split
+      .first()
+      .and_then(|t| OwnerType::from_str(t).ok())
+      .ok_or_else(|| invalid("the owner type has to be orgs or users"))?;
 
     let owner_name = split
       .get(1)
-      .ok_or(anyhow::anyhow!("Could not parse owner owner from url {s}"))?
+      .filter(|o| !o.is_empty())
+      .ok_or_else(|| invalid("missing owner"))?
       .clone();
 
+    if split.get(2).map(String::as_str) != Some("projects") {
+      return Err(invalid("missing projects segment").into());
+    }
+
     let number = split
       .get(3)
-      .ok_or(anyhow::anyhow!("Could not parse project id from url {s}"))?
-      .parse()?;
+      .and_then(|n| n.parse().ok())
+      .ok_or_else(|| invalid("the project number has to be a number"))?;
 
     Ok(Self {
       url: s.to_string(),
@@ -74,6 +82,10 @@ impl FromStr for Project {
 }
 
 impl Project {
+  pub fn url(&self) -> &str {
+    &self.url
+  }
+
   pub fn owner_name(&self) -> &str {
     &self.owner_name
   }
@@ -87,6 +99,29 @@ impl Project {
   }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectId(String);
+
+impl ProjectId {
+  pub fn new(id: String) -> Self {
+    Self(id)
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl Display for ProjectId {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+
+const DEFAULT_FIELD_NAME: &str = "Epic";
+const DEFAULT_BATCH_SIZE: usize = 50;
+const DEFAULT_PLAN_FILE: &str = "epic-plan.json";
+
 #[derive(Debug, Clone)]
 pub struct Args(pub Vec<String>);
 
@@ -96,33 +131,207 @@ pub struct Params {
   github_token: String,
   labels: Vec<String>,
   operator: Operator,
+  field_name: String,
+  on_close: ClosePolicy,
+  on_unlabeled: ClosePolicy,
+  state: StateFilter,
+  batch_size: usize,
+  start_page: u32,
+  run_mode: RunMode,
+  plan_file: String,
+  repositories: Vec<String>,
+  topic: Option<String>,
+  add_epic_item: bool,
+  dry_run: bool,
+  filter: Expression,
+  criteria: Criteria,
+  mode: Mode,
 }
 
 impl TryFrom<Args> for Params {
   type Error = anyhow::Error;
 
+  /// Validates every input and reports all invalid ones at once with
+  /// [`errors::Errors::InvalidInputs`].
   fn try_from(value: Args) -> Result<Self, Self::Error> {
+    let input = |i: usize| value.0.get(i).map(|s| s.trim()).filter(|s| !s.is_empty());
+    let mut validation = Validation::default();
+
+    let project = validation.required("project-url", input(1), Project::from_str);
+    let github_token = validation.required("github-token", input(2), |s| Ok(s.to_string()));
+    let labels = input(3)
+      .map(|s| {
+        s.split(',')
+          .map(|s| s.trim())
+          .filter(|s| !s.is_empty())
+          .map(String::from)
+          .collect::<Vec<_>>()
+      })
+      .unwrap_or_default();
+    let patterns = labels
+      .iter()
+      .filter_map(|label| validation.check("labels", Pattern::from_str(label)))
+      .collect::<Vec<_>>();
+    let operator = validation.optional("label-operator", input(4), Operator::from_str);
+    let on_close = validation.optional("on-close", input(6), ClosePolicy::from_str);
+    let add_epic_item = validation.optional("add-epic-item", input(7), parse_bool);
+    let dry_run = validation.optional("dry-run", input(8), parse_bool);
+    let expression =
+      input(9).and_then(|s| validation.check("label-expression", Expression::from_str(s)));
+    let pattern = |s: &str| Pattern::from_str(s).map(Some);
+    let title = validation.optional("title-pattern", input(10), pattern);
+    let body = validation.optional("body-pattern", input(11), pattern);
+    let milestone = validation.optional("milestone", input(12), pattern);
+    let issue_type = validation.optional("issue-type", input(13), pattern);
+    let assignee = validation.optional("assignee", input(14), pattern);
+    let author_associations = input(15)
+      .map(|s| {
+        s.split(',')
+          .map(|s| s.trim())
+          .filter(|s| !s.is_empty())
+          .filter_map(|s| validation.check("author-association", AuthorAssociation::from_str(s)))
+          .collect::<Vec<_>>()
+      })
+      .unwrap_or_default();
+    let mode = validation.optional("match", input(16), Mode::from_str);
+    let on_unlabeled = validation
+      .optional("on-unlabeled", input(17), |s| {
+        ClosePolicy::from_str(s).map(Some)
+      })
+      .unwrap_or(ClosePolicy::Remove);
+    let state = validation.optional("state", input(18), StateFilter::from_str);
+    let batch_size = validation
+      .optional("batch-size", input(19), |s| parse_positive(s).map(Some))
+      .unwrap_or(DEFAULT_BATCH_SIZE);
+    let start_page = validation
+      .optional("start-page", input(20), |s| parse_positive(s).map(Some))
+      .unwrap_or(1);
+    let run_mode = validation.optional("mode", input(21), RunMode::from_str);
+    let plan_file = input(22).unwrap_or(DEFAULT_PLAN_FILE).to_string();
+    let repositories = input(23)
+      .map(|s| {
+        s.split(',')
+          .map(|s| s.trim())
+          .filter(|s| !s.is_empty())
+          .filter_map(|s| validation.check("repositories", parse_repository(s)))
+          .collect::<Vec<_>>()
+      })
+      .unwrap_or_default();
+    let topic = input(24).map(String::from);
+
+    let (Some(project), Some(github_token), true) =
+      (project, github_token, validation.0.is_empty())
+    else {
+      return Err(validation.into_error());
+    };
+
+    let filter = match expression {
+      Some(expression) => expression,
+      None => Expression::from_labels(patterns, &operator),
+    };
+
     Ok(Self {
-      project: Project::from_str(value.0.get(1).context("Missing project-url parameter")?)?,
-      github_token: value
-        .0
-        .get(2)
-        .context("Missing github-token parameter")?
-        .clone(),
-      labels: value
-        .0
-        .get(3)
-        .map(|s| s.split(',').map(|s| s.trim()).map(String::from).collect())
-        .unwrap_or(vec![]),
-      operator: value
-        .0
-        .get(4)
-        .and_then(|s| Operator::from_str(s).ok())
-        .unwrap_or_default(),
+      project,
+      github_token,
+      labels,
+      operator,
+      field_name: input(5).unwrap_or(DEFAULT_FIELD_NAME).to_string(),
+      on_close,
+      on_unlabeled,
+      state,
+      batch_size,
+      start_page,
+      run_mode,
+      plan_file,
+      repositories,
+      topic,
+      add_epic_item,
+      dry_run,
+      filter,
+      criteria: Criteria::new(
+        title,
+        body,
+        milestone,
+        issue_type,
+        assignee,
+        author_associations,
+      ),
+      mode,
     })
   }
 }
 
+/// Collects the errors of all invalid inputs, so they can be reported at once.
+#[derive(Debug, Default)]
+struct Validation(Vec<(String, String)>);
+
+impl Validation {
+  fn check<T>(&mut self, input: &str, result: anyhow::Result<T, anyhow::Error>) -> Option<T> {
+    result
+      .map_err(|e| self.0.push((input.to_string(), e.to_string())))
+      .ok()
+  }
+
+  fn required<T>(
+    &mut self,
+    input: &str,
+    value: Option<&str>,
+    parse: impl FnOnce(&str) -> anyhow::Result<T, anyhow::Error>,
+  ) -> Option<T> {
+    let result = value
+      .ok_or_else(|| errors::Errors::MissingInput(input.to_string()).into())
+      .and_then(parse);
+    self.check(input, result)
+  }
+
+  /// Parses an input that falls back to its default when not set.
+  fn optional<T: Default>(
+    &mut self,
+    input: &str,
+    value: Option<&str>,
+    parse: impl FnOnce(&str) -> anyhow::Result<T, anyhow::Error>,
+  ) -> T {
+    value
+      .and_then(|v| self.check(input, parse(v)))
+      .unwrap_or_default()
+  }
+
+  fn into_error(self) -> anyhow::Error {
+    errors::Errors::InvalidInputs(self.0).into()
+  }
+}
+
+fn parse_positive<T: FromStr + Default + PartialOrd>(s: &str) -> anyhow::Result<T, anyhow::Error> {
+  s.parse()
+    .ok()
+    .filter(|n| n > &T::default())
+    .ok_or_else(|| errors::Errors::CouldNotParseNumber(s.to_string()).into())
+}
+
+/// Parses `<owner>/<name>` or `<name>`, which is a repository of the project owner.
+fn parse_repository(s: &str) -> anyhow::Result<String, anyhow::Error> {
+  let valid = |part: &str| {
+    !part.is_empty()
+      && part
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+  };
+
+  match s.split_once('/') {
+    Some((owner, name)) if valid(owner) && valid(name) => Ok(s.to_string()),
+    None if valid(s) => Ok(s.to_string()),
+    _ => Err(errors::Errors::CouldNotParseRepository(s.to_string()).into()),
+  }
+}
+
+fn parse_bool(s: &str) -> anyhow::Result<bool, anyhow::Error> {
+  match s.to_lowercase().as_str() {
+    "true" => Ok(true),
+    "false" => Ok(false),
+    _ => Err(errors::Errors::CouldNotParseBoolean(s.to_string()).into()),
+  }
+}
+
 impl Params {
   pub fn github_token(&self) -> &str {
     &self.github_token
@@ -139,11 +348,102 @@ impl Params {
   pub fn project(&self) -> &Project {
     &self.project
   }
+
+  pub fn field_name(&self) -> &str {
+    &self.field_name
+  }
+
+  pub fn on_close(&self) -> &ClosePolicy {
+    &self.on_close
+  }
+
+  /// What happens to the option when a label change makes the issue stop being an epic.
+  pub fn on_unlabeled(&self) -> &ClosePolicy {
+    &self.on_unlabeled
+  }
+
+  /// The state of the issues a backfill pages through.
+  pub fn state(&self) -> &StateFilter {
+    &self.state
+  }
+
+  /// How many options a backfill creates with one field update.
+  pub fn batch_size(&self) -> usize {
+    self.batch_size
+  }
+
+  /// The page of issues a backfill starts at, to resume an interrupted backfill.
+  pub fn start_page(&self) -> u32 {
+    self.start_page
+  }
+
+  /// What the action does on `workflow_dispatch` and `schedule` events.
+  pub fn run_mode(&self) -> &RunMode {
+    &self.run_mode
+  }
+
+  /// The plan file the `plan` mode writes and the `apply` mode executes.
+  pub fn plan_file(&self) -> &str {
+    &self.plan_file
+  }
+
+  /// The configured repositories to collect epics from, `<name>` or `<owner>/<name>`.
+  pub fn repositories(&self) -> &Vec<String> {
+    &self.repositories
+  }
+
+  /// The topic of the repositories of the project owner to collect epics from.
+  pub fn topic(&self) -> Option<&str> {
+    self.topic.as_deref()
+  }
+
+  pub fn add_epic_item(&self) -> bool {
+    self.add_epic_item
+  }
+
+  pub fn dry_run(&self) -> bool {
+    self.dry_run
+  }
+
+  /// The label filter, either the `label-expression` input or the translated `labels` and
+  /// `label-operator` inputs.
+  pub fn filter(&self) -> &Expression {
+    &self.filter
+  }
+
+  /// Classifies the event by evaluating the epic predicate before and after the label change of
+  /// a `labeled` or `unlabeled` event. Other events can't change the state.
+  pub fn transition(&self, event: &IssueEvent) -> Transition {
+    let is_epic = self.is_epic(event.issue());
+
+    match event.previous_issue().map(|issue| self.is_epic(&issue)) {
+      Some(false) if is_epic => Transition::BecameEpic,
+      Some(true) if !is_epic => Transition::StoppedBeingEpic,
+      _ => Transition::Unchanged(is_epic),
+    }
+  }
+
+  /// Whether `issue` is an epic. The label filter, if configured, and the configured criteria are
+  /// combined according to the `match` input. Without anything configured no issue is an epic.
+  pub fn is_epic(&self, issue: &Issue) -> bool {
+    let results = (!self.filter.is_empty())
+      .then(|| self.filter.matches(issue))
+      .into_iter()
+      .chain(self.criteria.evaluate(issue))
+      .collect::<Vec<_>>();
+
+    !results.is_empty()
+      && match self.mode {
+        Mode::Any => results.iter().any(|r| *r),
+        Mode::All => results.iter().all(|r| *r),
+      }
+  }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum Operator {
   And,
+  #[default]
   Or,
   Not,
 }
@@ -152,7 +452,7 @@ impl FromStr for Operator {
   type Err = anyhow::Error;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    match s {
+    match s.trim().to_uppercase().as_str() {
       "AND" => Ok(Self::And),
       "OR" => Ok(Self::Or),
       "NOT" => Ok(Self::Not),
@@ -161,28 +461,337 @@ impl FromStr for Operator {
   }
 }
 
-impl Default for Operator {
-  fn default() -> Self {
-    Self::Or
+/// What happens to the option of an epic when the issue is closed. Reopening the issue reverts it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ClosePolicy {
+  #[default]
+  Keep,
+  RenameWithSuffix,
+  Recolor,
+  Remove,
+}
+
+impl FromStr for ClosePolicy {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_lowercase().as_str() {
+      "keep" => Ok(Self::Keep),
+      "rename-with-suffix" => Ok(Self::RenameWithSuffix),
+      "recolor" => Ok(Self::Recolor),
+      "remove" => Ok(Self::Remove),
+      _ => Err(errors::Errors::CouldNotParseClosePolicy(s.to_string()).into()),
+    }
+  }
+}
+
+impl Display for Operator {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Operator::And => write!(f, "AND"),
+      Operator::Or => write!(f, "OR"),
+      Operator::Not => write!(f, "NOT"),
+    }
+  }
+}
+
+/// The action of an `issues` webhook event. Actions GitHub adds later deserialize to `Other`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+  Opened,
+  Edited,
+  Deleted,
+  Transferred,
+  Pinned,
+  Unpinned,
+  Closed,
+  Reopened,
+  Assigned,
+  Unassigned,
+  Labeled,
+  Unlabeled,
+  Locked,
+  Unlocked,
+  Milestoned,
+  Demilestoned,
+  Typed,
+  Untyped,
+  #[serde(other)]
+  Other,
+}
+
+impl Display for Action {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Action::Opened => write!(f, "opened"),
+      Action::Edited => write!(f, "edited"),
+      Action::Deleted => write!(f, "deleted"),
+      Action::Transferred => write!(f, "transferred"),
+      Action::Pinned => write!(f, "pinned"),
+      Action::Unpinned => write!(f, "unpinned"),
+      Action::Closed => write!(f, "closed"),
+      Action::Reopened => write!(f, "reopened"),
+      Action::Assigned => write!(f, "assigned"),
+      Action::Unassigned => write!(f, "unassigned"),
+      Action::Labeled => write!(f, "labeled"),
+      Action::Unlabeled => write!(f, "unlabeled"),
+      Action::Locked => write!(f, "locked"),
+      Action::Unlocked => write!(f, "unlocked"),
+      Action::Milestoned => write!(f, "milestoned"),
+      Action::Demilestoned => write!(f, "demilestoned"),
+      Action::Typed => write!(f, "typed"),
+      Action::Untyped => write!(f, "untyped"),
+      Action::Other => write!(f, "unknown"),
+    }
+  }
+}
+
+/// The event that triggered the workflow.
+#[derive(Debug)]
+pub enum Event {
+  Issues(IssueEvent),
+  PullRequest(PullRequestEvent),
+  IssueComment(IssueCommentEvent),
+  /// `workflow_dispatch` and `schedule` events, which run a backfill of the whole repository.
+  Scheduled,
+}
+
+impl Event {
+  /// Parses the payload according to `GITHUB_EVENT_NAME`.
+  pub fn parse(name: &str, payload: &str) -> anyhow::Result<Self, anyhow::Error> {
+    match name {
+      "issues" => Ok(Event::Issues(serde_json::from_str(payload)?)),
+      "pull_request" | "pull_request_target" => {
+        Ok(Event::PullRequest(serde_json::from_str(payload)?))
+      }
+      "issue_comment" => Ok(Event::IssueComment(serde_json::from_str(payload)?)),
+      "workflow_dispatch" | "schedule" => Ok(Event::Scheduled),
+      _ => Err(errors::Errors::UnsupportedEvent(name.to_string()).into()),
+    }
   }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct IssueEvent {
+  action: Action,
+  #[serde(default)]
+  changes: Changes,
   issue: Issue,
+  #[serde(default)]
+  label: Option<Label>,
+  #[serde(default)]
+  repository: Option<Repository>,
+  #[serde(default)]
+  sender: Option<User>,
 }
 
 impl IssueEvent {
+  pub fn action(&self) -> &Action {
+    &self.action
+  }
+
+  pub fn changes(&self) -> &Changes {
+    &self.changes
+  }
+
+  pub fn issue(&self) -> &Issue {
+    &self.issue
+  }
+
+  pub fn repository(&self) -> Option<&Repository> {
+    self.repository.as_ref()
+  }
+
+  /// The user that triggered the event.
+  pub fn sender(&self) -> Option<&User> {
+    self.sender.as_ref()
+  }
+
+  /// The label that was added or removed by a `labeled` or `unlabeled` event.
+  pub fn label(&self) -> Option<&Label> {
+    self.label.as_ref()
+  }
+
+  /// The issue as it was before the label of a `labeled` or `unlabeled` event changed.
+  pub fn previous_issue(&self) -> Option<Issue> {
+    let label = self.label.as_ref()?;
+    let labels = match self.action {
+      Action::Labeled => self
+        .issue
+        .labels
+        .iter()
+        .filter(|l| l.name != label.name)
+        .cloned()
+        .collect(),
+      Action::Unlabeled => self
+        .issue
+        .labels
+        .iter()
+        .cloned()
+        .chain([label.clone()])
+        .collect(),
+      _ => return None,
+    };
+
+    Some(Issue {
+      labels,
+      ..self.issue.clone()
+    })
+  }
+}
+
+/// A `pull_request` webhook event. Pull requests carry the same attributes the epic predicate
+/// needs, so they are handled as issues.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PullRequestEvent {
+  action: Action,
+  #[serde(default)]
+  changes: Changes,
+  pull_request: Issue,
+  #[serde(default)]
+  label: Option<Label>,
+  #[serde(default)]
+  repository: Option<Repository>,
+  #[serde(default)]
+  sender: Option<User>,
+}
+
+impl From<PullRequestEvent> for IssueEvent {
+  fn from(value: PullRequestEvent) -> Self {
+    Self {
+      action: value.action,
+      changes: value.changes,
+      issue: value.pull_request,
+      label: value.label,
+      repository: value.repository,
+      sender: value.sender,
+    }
+  }
+}
+
+/// An `issue_comment` webhook event, used for `/epic` commands.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IssueCommentEvent {
+  action: CommentAction,
+  issue: Issue,
+  comment: Comment,
+  #[serde(default)]
+  repository: Option<Repository>,
+}
+
+impl IssueCommentEvent {
+  pub fn action(&self) -> &CommentAction {
+    &self.action
+  }
+
   pub fn issue(&self) -> &Issue {
     &self.issue
   }
+
+  pub fn comment(&self) -> &Comment {
+    &self.comment
+  }
+
+  pub fn repository(&self) -> Option<&Repository> {
+    self.repository.as_ref()
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentAction {
+  Created,
+  Edited,
+  Deleted,
+  #[serde(other)]
+  Other,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Comment {
+  id: u64,
+  node_id: String,
+  body: String,
+  user: User,
+}
+
+impl Comment {
+  pub fn node_id(&self) -> &str {
+    &self.node_id
+  }
+
+  pub fn body(&self) -> &str {
+    &self.body
+  }
+
+  pub fn user(&self) -> &User {
+    &self.user
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Changes {
+  title: Option<Change>,
+  body: Option<Change>,
+  new_repository: Option<Repository>,
+  new_issue: Option<Issue>,
+}
+
+impl Changes {
+  /// The issue a `transferred` issue became in the new repository.
+  pub fn new_issue(&self) -> Option<&Issue> {
+    self.new_issue.as_ref()
+  }
+
+  /// The repository a `transferred` issue was moved to.
+  pub fn new_repository(&self) -> Option<&Repository> {
+    self.new_repository.as_ref()
+  }
+
+  pub fn title(&self) -> Option<&Change> {
+    self.title.as_ref()
+  }
+
+  pub fn body(&self) -> Option<&Change> {
+    self.body.as_ref()
+  }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+pub struct Change {
+  from: String,
+}
+
+impl Change {
+  pub fn from(&self) -> &str {
+    &self.from
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Issue {
   id: u64,
+  node_id: String,
   labels: Vec<Label>,
   number: u32,
+  title: String,
+  #[serde(default)]
+  body: Option<String>,
+  #[serde(default)]
+  milestone: Option<Milestone>,
+  #[serde(default, rename = "type")]
+  issue_type: Option<IssueType>,
+  #[serde(default)]
+  assignees: Vec<User>,
+  #[serde(default)]
+  author_association: AuthorAssociation,
+  #[serde(default)]
+  state: IssueState,
+  /// Only set for pull requests listed by the issues API.
+  #[serde(default)]
+  pull_request: Option<serde_json::Value>,
+  html_url: String,
 }
 
 impl Issue {
@@ -190,6 +799,10 @@ impl Issue {
     self.id
   }
 
+  pub fn node_id(&self) -> &str {
+    &self.node_id
+  }
+
   pub fn labels(&self) -> impl Iterator<Item = &Label> + '_ {
     self.labels.iter()
   }
@@ -197,69 +810,1273 @@ impl Issue {
   pub fn number(&self) -> u32 {
     self.number
   }
+
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+
+  pub fn body(&self) -> Option<&str> {
+    self.body.as_deref()
+  }
+
+  /// The title of the milestone of the issue.
+  pub fn milestone(&self) -> Option<&str> {
+    self.milestone.as_ref().map(|m| m.title.as_str())
+  }
+
+  /// The name of the issue type of the issue.
+  pub fn issue_type(&self) -> Option<&str> {
+    self.issue_type.as_ref().map(|t| t.name.as_str())
+  }
+
+  /// The logins of the assignees of the issue.
+  pub fn assignees(&self) -> impl Iterator<Item = &str> + '_ {
+    self.assignees.iter().map(|a| a.login.as_str())
+  }
+
+  pub fn author_association(&self) -> &AuthorAssociation {
+    &self.author_association
+  }
+
+  pub fn state(&self) -> &IssueState {
+    &self.state
+  }
+
+  pub fn is_pull_request(&self) -> bool {
+    self.pull_request.is_some()
+  }
+
+  pub fn html_url(&self) -> &str {
+    &self.html_url
+  }
+
+  /// The full name of the repository of the issue, read from its url.
+  pub fn repository(&self) -> Option<&str> {
+    let (_, path) = self.html_url.split_once("://")?.1.split_once('/')?;
+    let (end, _) = path.match_indices('/').nth(1)?;
+
+    Some(&path[..end])
+  }
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
-pub struct Label {
-  id: u64,
-  name: String,
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+  #[default]
+  Open,
+  Closed,
 }
 
-impl Label {
-  pub fn name(&self) -> &str {
-    &self.name
+/// What the action does on `workflow_dispatch` and `schedule` events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunMode {
+  /// Creates the missing options of all epics.
+  #[default]
+  Backfill,
+  /// Reconciles the options with all epics, see [`crate::reconcile`].
+  Sync,
+  /// Writes the reconciliation diff to the plan file, see [`crate::plan`].
+  Plan,
+  /// Executes the plan file.
+  Apply,
+  /// Reports items, options and epics that are out of sync, see [`crate::audit`].
+  Audit,
+}
+
+impl FromStr for RunMode {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_lowercase().as_str() {
+      "backfill" => Ok(Self::Backfill),
+      "sync" => Ok(Self::Sync),
+      "plan" => Ok(Self::Plan),
+      "apply" => Ok(Self::Apply),
+      "audit" => Ok(Self::Audit),
+      _ => Err(errors::Errors::CouldNotParseRunMode(s.to_string()).into()),
+    }
   }
 }
 
-#[cfg(test)]
-mod test {
-  use std::str::FromStr;
+/// Which issues a backfill pages through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StateFilter {
+  Open,
+  Closed,
+  #[default]
+  All,
+}
 
-  use crate::models::{self, Label, OwnerType};
+impl FromStr for StateFilter {
+  type Err = anyhow::Error;
 
-  use super::Project;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_lowercase().as_str() {
+      "open" => Ok(Self::Open),
+      "closed" => Ok(Self::Closed),
+      "all" => Ok(Self::All),
+      _ => Err(errors::Errors::CouldNotParseState(s.to_string()).into()),
+    }
+  }
+}
 
-  const ISSUE_EVENT: &str = "{\n  \"action\": \"opened\",\n  \"issue\": {\n    \"active_lock_reason\": null,\n    \"assignee\": null,\n    \"assignees\": [],\n    \"author_association\": \"OWNER\",\n    \"body\": null,\n    \"closed_at\": null,\n    \"comments\": 0,\n    \"comments_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/comments\",\n    \"created_at\": \"2023-03-06T13:14:03Z\",\n    \"events_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/events\",\n    \"html_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/19\",\n    \"id\": 1611401201,\n    \"labels\": [],\n    \"labels_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/labels{/name}\",\n    \"locked\": false,\n    \"milestone\": null,\n    \"node_id\": \"I_kwDOJEXPh85gDAfx\",\n    \"number\": 19,\n    \"performed_via_github_app\": null,\n    \"reactions\": {\n      \"+1\": 0,\n      \"-1\": 0,\n      \"confused\": 0,\n      \"eyes\": 0,\n      \"heart\": 0,\n      \"hooray\": 0,\n      \"laugh\": 0,\n      \"rocket\": 0,\n      \"total_count\": 0,\n      \"url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/reactions\"\n    },\n    \"repository_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project\",\n    \"state\": \"open\",\n    \"state_reason\": null,\n    \"timeline_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/timeline\",\n    \"title\": \"Test19\",\n    \"updated_at\": \"2023-03-06T13:14:03Z\",\n    \"url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19\",\n    \"user\": {\n      \"avatar_url\": \"https://avatars.githubusercontent.com/u/33718194?v=4\",\n      \"events_url\": \"https://api.github.com/users/JenSeReal/events{/privacy}\",\n      \"followers_url\": \"https://api.github.com/users/JenSeReal/followers\",\n      \"following_url\": \"https://api.github.com/users/JenSeReal/following{/other_user}\",\n      \"gists_url\": \"https://api.github.com/users/JenSeReal/gists{/gist_id}\",\n      \"gravatar_id\": \"\",\n      \"html_url\": \"https://github.com/JenSeReal\",\n      \"id\": 33718194,\n      \"login\": \"JenSeReal\",\n      \"node_id\": \"MDQ6VXNlcjMzNzE4MTk0\",\n      \"organizations_url\": \"https://api.github.com/users/JenSeReal/orgs\",\n      \"received_events_url\": \"https://api.github.com/users/JenSeReal/received_events\",\n      \"repos_url\": \"https://api.github.com/users/JenSeReal/repos\",\n      \"site_admin\": false,\n      \"starred_url\": \"https://api.github.com/users/JenSeReal/starred{/owner}{/repo}\",\n      \"subscriptions_url\": \"https://api.github.com/users/JenSeReal/subscriptions\",\n      \"type\": \"User\",\n      \"url\": \"https://api.github.com/users/JenSeReal\"\n    }\n  },\n  \"repository\": {\n    \"allow_forking\": true,\n    \"archive_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/{archive_format}{/ref}\",\n    \"archived\": false,\n    \"assignees_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/assignees{/user}\",\n    \"blobs_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/blobs{/sha}\",\n    \"branches_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/branches{/branch}\",\n    \"clone_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project.git\",\n    \"collaborators_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/collaborators{/collaborator}\",\n    \"comments_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/comments{/number}\",\n    \"commits_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/commits{/sha}\",\n    \"compare_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/compare/{base}...{head}\",\n    \"contents_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/contents/{+path}\",\n    \"contributors_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/contributors\",\n    \"created_at\": \"2023-03-02T09:03:40Z\",\n    \"default_branch\": \"main\",\n    \"deployments_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/deployments\",\n    \"description\": null,\n    \"disabled\": false,\n    \"downloads_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/downloads\",\n    \"events_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/events\",\n    \"fork\": false,\n    \"forks\": 0,\n    \"forks_count\": 0,\n    \"forks_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/forks\",\n    \"full_name\": \"JenSeReal/example-add-epic-issue-to-project\",\n    \"git_commits_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/commits{/sha}\",\n    \"git_refs_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/refs{/sha}\",\n    \"git_tags_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/tags{/sha}\",\n    \"git_url\": \"git://github.com/JenSeReal/example-add-epic-issue-to-project.git\",\n    \"has_discussions\": false,\n    \"has_downloads\": true,\n    \"has_issues\": true,\n    \"has_pages\": false,\n    \"has_projects\": true,\n    \"has_wiki\": false,\n    \"homepage\": null,\n    \"hooks_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/hooks\",\n    \"html_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project\",\n    \"id\": 608554887,\n    \"is_template\": false,\n    \"issue_comment_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/comments{/number}\",\n    \"issue_events_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/events{/number}\",\n    \"issues_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues{/number}\",\n    \"keys_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/keys{/key_id}\",\n    \"labels_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/labels{/name}\",\n    \"language\": null,\n    \"languages_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/languages\",\n    \"license\": {\n      \"key\": \"mit\",\n      \"name\": \"MIT License\",\n      \"node_id\": \"MDc6TGljZW5zZTEz\",\n      \"spdx_id\": \"MIT\",\n      \"url\": \"https://api.github.com/licenses/mit\"\n    },\n    \"merges_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/merges\",\n    \"milestones_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/milestones{/number}\",\n    \"mirror_url\": null,\n    \"name\": \"example-add-epic-issue-to-project\",\n    \"node_id\": \"R_kgDOJEXPhw\",\n    \"notifications_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/notifications{?since,all,participating}\",\n    \"open_issues\": 19,\n    \"open_issues_count\": 19,\n    \"owner\": {\n      \"avatar_url\": \"https://avatars.githubusercontent.com/u/33718194?v=4\",\n      \"events_url\": \"https://api.github.com/users/JenSeReal/events{/privacy}\",\n      \"followers_url\": \"https://api.github.com/users/JenSeReal/followers\",\n      \"following_url\": \"https://api.github.com/users/JenSeReal/following{/other_user}\",\n      \"gists_url\": \"https://api.github.com/users/JenSeReal/gists{/gist_id}\",\n      \"gravatar_id\": \"\",\n      \"html_url\": \"https://github.com/JenSeReal\",\n      \"id\": 33718194,\n      \"login\": \"JenSeReal\",\n      \"node_id\": \"MDQ6VXNlcjMzNzE4MTk0\",\n      \"organizations_url\": \"https://api.github.com/users/JenSeReal/orgs\",\n      \"received_events_url\": \"https://api.github.com/users/JenSeReal/received_events\",\n      \"repos_url\": \"https://api.github.com/users/JenSeReal/repos\",\n      \"site_admin\": false,\n      \"starred_url\": \"https://api.github.com/users/JenSeReal/starred{/owner}{/repo}\",\n      \"subscriptions_url\": \"https://api.github.com/users/JenSeReal/subscriptions\",\n      \"type\": \"User\",\n      \"url\": \"https://api.github.com/users/JenSeReal\"\n    },\n    \"private\": true,\n    \"pulls_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/pulls{/number}\",\n    \"pushed_at\": \"2023-03-06T08:52:13Z\",\n    \"releases_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/releases{/id}\",\n    \"size\": 6,\n    \"ssh_url\": \"git@github.com:JenSeReal/example-add-epic-issue-to-project.git\",\n    \"stargazers_count\": 0,\n    \"stargazers_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/stargazers\",\n    \"statuses_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/statuses/{sha}\",\n    \"subscribers_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/subscribers\",\n    \"subscription_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/subscription\",\n    \"svn_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project\",\n    \"tags_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/tags\",\n    \"teams_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/teams\",\n    \"topics\": [],\n    \"trees_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/trees{/sha}\",\n    \"updated_at\": \"2023-03-02T09:03:40Z\",\n    \"url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project\",\n    \"visibility\": \"private\",\n    \"watchers\": 0,\n    \"watchers_count\": 0,\n    \"web_commit_signoff_required\": false\n  },\n  \"sender\": {\n    \"avatar_url\": \"https://avatars.githubusercontent.com/u/33718194?v=4\",\n    \"events_url\": \"https://api.github.com/users/JenSeReal/events{/privacy}\",\n    \"followers_url\": \"https://api.github.com/users/JenSeReal/followers\",\n    \"following_url\": \"https://api.github.com/users/JenSeReal/following{/other_user}\",\n    \"gists_url\": \"https://api.github.com/users/JenSeReal/gists{/gist_id}\",\n    \"gravatar_id\": \"\",\n    \"html_url\": \"https://github.com/JenSeReal\",\n    \"id\": 33718194,\n    \"login\": \"JenSeReal\",\n    \"node_id\": \"MDQ6VXNlcjMzNzE4MTk0\",\n    \"organizations_url\": \"https://api.github.com/users/JenSeReal/orgs\",\n    \"received_events_url\": \"https://api.github.com/users/JenSeReal/received_events\",\n    \"repos_url\": \"https://api.github.com/users/JenSeReal/repos\",\n    \"site_admin\": false,\n    \"starred_url\": \"https://api.github.com/users/JenSeReal/starred{/owner}{/repo}\",\n    \"subscriptions_url\": \"https://api.github.com/users/JenSeReal/subscriptions\",\n    \"type\": \"User\",\n    \"url\": \"https://api.github.com/users/JenSeReal\"\n  }\n}";
+impl Display for StateFilter {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      StateFilter::Open => write!(f, "open"),
+      StateFilter::Closed => write!(f, "closed"),
+      StateFilter::All => write!(f, "all"),
+    }
+  }
+}
 
-  const PROJECT_URL: &str = "https://github.com/users/JenSeReal/projects/3";
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Milestone {
+  title: String,
+}
 
-  const PROJECT_URL_ORG: &str = "https://github.com/orgs/JenSeReal/projects/3";
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssueType {
+  name: String,
+}
 
-  #[test]
-  fn test_deserealizazion_issue_event() -> anyhow::Result<(), anyhow::Error> {
-    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
-    let labels: Vec<Label> = vec![];
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Repository {
+  id: u64,
+  node_id: String,
+  name: String,
+  full_name: String,
+  html_url: String,
+  owner: User,
+}
 
-    assert_eq!(event.issue().id(), 1611401201);
-    assert_eq!(event.issue().labels().cloned().collect::<Vec<_>>(), labels);
-    assert_eq!(event.issue().number(), 19);
+impl Repository {
+  pub fn node_id(&self) -> &str {
+    &self.node_id
+  }
 
-    Ok(())
+  pub fn name(&self) -> &str {
+    &self.name
   }
 
-  #[test]
-  fn deserialize_project_url() -> anyhow::Result<(), anyhow::Error> {
-    let project_url = Project::from_str(PROJECT_URL)?;
+  /// The name including the owner, e.g. `JenSeReal/add-epic-to-project`.
+  pub fn full_name(&self) -> &str {
+    &self.full_name
+  }
 
-    assert_eq!(project_url.url, PROJECT_URL.to_string());
+  pub fn html_url(&self) -> &str {
+    &self.html_url
+  }
 
-    assert_eq!(project_url.owner_type, OwnerType::Users);
-    assert_eq!(project_url.owner_name, "JenSeReal".to_string());
-    assert_eq!(project_url.number, 3);
+  pub fn owner(&self) -> &User {
+    &self.owner
+  }
+}
 
-    Ok(())
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct User {
+  login: String,
+}
+
+impl User {
+  pub fn login(&self) -> &str {
+    &self.login
   }
+}
 
-  #[test]
-  fn deserialize_project_url_org() -> anyhow::Result<(), anyhow::Error> {
-    let project_url = Project::from_str(PROJECT_URL_ORG)?;
+/// How the author of an issue is associated with the repository.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuthorAssociation {
+  Owner,
+  Member,
+  Collaborator,
+  Contributor,
+  FirstTimeContributor,
+  FirstTimer,
+  Mannequin,
+  #[default]
+  #[serde(other)]
+  None,
+}
 
-    assert_eq!(project_url.url, PROJECT_URL_ORG.to_string());
+impl FromStr for AuthorAssociation {
+  type Err = anyhow::Error;
 
-    assert_eq!(project_url.owner_type, OwnerType::Organizations);
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim().to_uppercase().replace('-', "_").as_str() {
+      "OWNER" => Ok(Self::Owner),
+      "MEMBER" => Ok(Self::Member),
+      "COLLABORATOR" => Ok(Self::Collaborator),
+      "CONTRIBUTOR" => Ok(Self::Contributor),
+      "FIRST_TIME_CONTRIBUTOR" => Ok(Self::FirstTimeContributor),
+      "FIRST_TIMER" => Ok(Self::FirstTimer),
+      "MANNEQUIN" => Ok(Self::Mannequin),
+      "NONE" => Ok(Self::None),
+      _ => Err(errors::Errors::CouldNotParseAuthorAssociation(s.to_string()).into()),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Label {
+  id: u64,
+  name: String,
+}
+
+impl Label {
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+}
+
+const ISSUE_MARKER: &str = "issue-id:";
+/// Marks the title the option was named after, quoted like a JSON string.
+const TITLE_MARKER: &str = "title:";
+const CLOSED_MARKER: &str = "✅";
+const CLOSED_COLOR: Color = Color::Purple;
+/// Marks the color a recolored option had before its issue was closed.
+const PREVIOUS_COLOR_MARKER: &str = "previous-color:";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Color {
+  Blue,
+  #[default]
+  Gray,
+  Green,
+  Orange,
+  Pink,
+  Purple,
+  Red,
+  Yellow,
+}
+
+impl FromStr for Color {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Ok(serde_json::from_value(serde_json::Value::String(
+      s.trim().to_uppercase(),
+    ))?)
+  }
+}
+
+impl Display for Color {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Color::Blue => write!(f, "BLUE"),
+      Color::Gray => write!(f, "GRAY"),
+      Color::Green => write!(f, "GREEN"),
+      Color::Orange => write!(f, "ORANGE"),
+      Color::Pink => write!(f, "PINK"),
+      Color::Purple => write!(f, "PURPLE"),
+      Color::Red => write!(f, "RED"),
+      Color::Yellow => write!(f, "YELLOW"),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct SingleSelectField {
+  project_id: ProjectId,
+  id: String,
+  name: String,
+  options: Vec<SingleSelectOption>,
+}
+
+impl SingleSelectField {
+  pub fn new(
+    project_id: ProjectId,
+    id: String,
+    name: String,
+    options: Vec<SingleSelectOption>,
+  ) -> Self {
+    Self {
+      project_id,
+      id,
+      name,
+      options,
+    }
+  }
+
+  /// The project the field belongs to.
+  pub fn project_id(&self) -> &ProjectId {
+    &self.project_id
+  }
+
+  pub fn id(&self) -> &str {
+    &self.id
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn options(&self) -> &Vec<SingleSelectOption> {
+    &self.options
+  }
+
+  /// The option that was created for the issue. Options created before the issue marker was
+  /// introduced are matched by their name instead.
+  pub fn option_for(&self, issue: &Issue) -> Option<&SingleSelectOption> {
+    self.find_option(issue, issue.title())
+  }
+
+  /// Like [`SingleSelectField::option_for`], but matches options without a marker by
+  /// `legacy_name`, e.g. the title of the issue before it was edited.
+  pub fn find_option(&self, issue: &Issue, legacy_name: &str) -> Option<&SingleSelectOption> {
+    self
+      .options
+      .iter()
+      .find(|o| o.issue_id() == Some(issue.id()))
+      .or_else(|| {
+        self
+          .options
+          .iter()
+          .find(|o| o.issue_id().is_none() && o.name() == legacy_name)
+      })
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SingleSelectOption {
+  id: Option<String>,
+  name: String,
+  color: Color,
+  description: String,
+}
+
+impl SingleSelectOption {
+  pub fn new(id: Option<String>, name: String, color: Color, description: String) -> Self {
+    Self {
+      id,
+      name,
+      color,
+      description,
+    }
+  }
+
+  /// The option of the issue, named after its title. When the title collides with the name of
+  /// one of the `others` epic options, e.g. an epic with the same title in another repository,
+  /// the option is named `repo#number Title` instead. Names differing only in case are hard to
+  /// tell apart in the project, so they collide too. The description records the title, so a
+  /// sync can tell a changed title from a custom name.
+  pub fn for_issue(issue: &Issue, others: &[SingleSelectOption]) -> Self {
+    let repository = issue.repository().unwrap_or_default();
+    let title = issue.title().to_lowercase();
+    let collides = others.iter().any(|o| {
+      o.issue_id().map_or(false, |id| id != issue.id()) && o.base_name().to_lowercase() == title
+    });
+    let name = match collides {
+      true => format!(
+        "{}#{} {}",
+        repository.rsplit('/').next().unwrap_or_default(),
+        issue.number(),
+        issue.title()
+      ),
+      false => issue.title().to_string(),
+    };
+
+    Self::new(
+      None,
+      name,
+      Color::default(),
+      format!(
+        "{repository}#{} {ISSUE_MARKER}{} {TITLE_MARKER}{}",
+        issue.number(),
+        issue.id(),
+        serde_json::Value::from(issue.title())
+      ),
+    )
+  }
+
+  /// The same option, named after the current title of the issue like
+  /// [`SingleSelectOption::for_issue`].
+  pub fn renamed(&self, issue: &Issue, others: &[SingleSelectOption]) -> Self {
+    Self {
+      id: self.id.clone(),
+      color: self.color,
+      ..Self::for_issue(issue, others)
+    }
+  }
+
+  /// The option an epic issue should have in its current state, `None` for a closed issue whose
+  /// option is removed by the close policy.
+  pub fn desired(
+    issue: &Issue,
+    policy: &ClosePolicy,
+    others: &[SingleSelectOption],
+  ) -> Option<Self> {
+    let option = Self::for_issue(issue, others);
+
+    match issue.state() {
+      IssueState::Open => Some(option),
+      IssueState::Closed if policy == &ClosePolicy::Remove => None,
+      IssueState::Closed => Some(option.closed(policy)),
+    }
+  }
+
+  /// The same option with the name, color and description of `desired`. A recolored `desired`
+  /// remembers the color this option had before its issue was closed.
+  pub fn updated(&self, desired: &Self) -> Self {
+    let description = match desired.description.rsplit_once(PREVIOUS_COLOR_MARKER) {
+      Some((description, _)) => format!(
+        "{description}{PREVIOUS_COLOR_MARKER}{}",
+        self.previous_color().unwrap_or(self.color)
+      ),
+      None => desired.description.clone(),
+    };
+
+    Self {
+      id: self.id.clone(),
+      description,
+      ..desired.clone()
+    }
+  }
+
+  /// The same option for another issue, e.g. the new issue of a transferred one, or with the
+  /// marker of the issue if it was created before the marker. Name, color and the rest of the
+  /// description are kept.
+  pub fn rekeyed(&self, issue: &Issue) -> Self {
+    Self {
+      description: format!(
+        "{}{}",
+        Self::for_issue(issue, &[]).description,
+        self.title_and_rest().1
+      ),
+      ..self.clone()
+    }
+  }
+
+  /// The same option with the name, color and description of `desired`, like
+  /// [`SingleSelectOption::updated`], but keeping custom names and colors. The name only follows
+  /// `desired` when the title of the issue changed, or isn't recorded yet, and otherwise only
+  /// gains or loses the mark of [`ClosePolicy::RenameWithSuffix`]. The color only changes when
+  /// [`ClosePolicy::Recolor`] recolors the option or reverts that.
+  pub fn reconciled(&self, desired: &Self) -> Self {
+    let title = self.title_and_rest().0;
+    let name = match title.is_some() && title == desired.title_and_rest().0 {
+      true => {
+        let name = self.closed_name().unwrap_or(&self.name);
+        match desired.closed_name() {
+          Some(_) => format!("{CLOSED_MARKER} {name}"),
+          None => name.to_string(),
+        }
+      }
+      false => desired.name.clone(),
+    };
+    let color = match (desired.previous_color(), self.previous_color()) {
+      (Some(_), _) => desired.color,
+      (None, Some(previous)) => previous,
+      (None, None) => self.color,
+    };
+
+    Self {
+      name,
+      color,
+      ..self.updated(desired)
+    }
+  }
+
+  /// The same option with a custom name.
+  pub fn named(&self, name: &str) -> Self {
+    Self {
+      name: name.to_string(),
+      ..self.clone()
+    }
+  }
+
+  /// The option of a closed issue, marked according to the close policy. A recolored option
+  /// remembers its color in the description, so reopening restores it.
+  pub fn closed(&self, policy: &ClosePolicy) -> Self {
+    match policy {
+      ClosePolicy::RenameWithSuffix if self.closed_name().is_none() => Self {
+        name: format!("{CLOSED_MARKER} {}", self.name),
+        ..self.clone()
+      },
+      ClosePolicy::Recolor if self.previous_color().is_none() => Self {
+        color: CLOSED_COLOR,
+        description: format!("{} {PREVIOUS_COLOR_MARKER}{}", self.description, self.color),
+        ..self.clone()
+      },
+      _ => self.clone(),
+    }
+  }
+
+  /// Reverts [`SingleSelectOption::closed`] after the issue was reopened, keeping custom names
+  /// and colors.
+  pub fn reopened(&self, policy: &ClosePolicy) -> Self {
+    match policy {
+      ClosePolicy::RenameWithSuffix => Self {
+        name: self.closed_name().unwrap_or(&self.name).to_string(),
+        ..self.clone()
+      },
+      ClosePolicy::Recolor => match self.description.rsplit_once(PREVIOUS_COLOR_MARKER) {
+        Some((description, _)) => Self {
+          color: self.previous_color().unwrap_or_default(),
+          description: description.trim_end().to_string(),
+          ..self.clone()
+        },
+        None => self.clone(),
+      },
+      ClosePolicy::Keep | ClosePolicy::Remove => self.clone(),
+    }
+  }
+
+  /// The name without the mark of [`ClosePolicy::RenameWithSuffix`].
+  fn closed_name(&self) -> Option<&str> {
+    self
+      .name
+      .strip_prefix(CLOSED_MARKER)
+      .map(|name| name.trim_start())
+  }
+
+  /// The name without the mark of [`ClosePolicy::RenameWithSuffix`] and the `repo#number` prefix
+  /// of a colliding name.
+  fn base_name(&self) -> &str {
+    let name = self.closed_name().unwrap_or(&self.name);
+    let Some(number) = self.issue_number() else {
+      return name;
+    };
+    let repository = self.repository().unwrap_or_default();
+    let prefix = format!(
+      "{}#{number} ",
+      repository.rsplit('/').next().unwrap_or_default()
+    );
+
+    name.strip_prefix(prefix.as_str()).unwrap_or(name)
+  }
+
+  /// The color before [`ClosePolicy::Recolor`] recolored the option.
+  fn previous_color(&self) -> Option<Color> {
+    let (_, marker) = self.description.rsplit_once(PREVIOUS_COLOR_MARKER)?;
+
+    marker.split_whitespace().next()?.parse().ok()
+  }
+
+  /// The title the option was named after, if the description records it, and the rest of the
+  /// description after the issue marker and the title, e.g. the previous color.
+  fn title_and_rest(&self) -> (Option<String>, &str) {
+    let Some((_, marker)) = self.description.split_once(ISSUE_MARKER) else {
+      return (None, "");
+    };
+    let rest = marker.trim_start_matches(|c: char| c.is_ascii_digit());
+    let Some(quoted) = rest.trim_start().strip_prefix(TITLE_MARKER) else {
+      return (None, rest);
+    };
+
+    let mut titles = serde_json::Deserializer::from_str(quoted).into_iter::<String>();
+    match titles.next() {
+      Some(Ok(title)) => (Some(title), &quoted[titles.byte_offset()..]),
+      _ => (None, rest),
+    }
+  }
+
+  /// The id of the issue this option was created for, read from the marker in the description.
+  pub fn issue_id(&self) -> Option<u64> {
+    let (_, marker) = self.description.split_once(ISSUE_MARKER)?;
+
+    marker
+      .split(|c: char| !c.is_ascii_digit())
+      .next()
+      .and_then(|id| id.parse().ok())
+  }
+
+  /// The number of the issue this option was created for, read from the marker in the
+  /// description.
+  pub fn issue_number(&self) -> Option<u32> {
+    let (reference, _) = self.description.split_once(ISSUE_MARKER)?;
+    let (_, number) = reference.trim().rsplit_once('#')?;
+
+    number.parse().ok()
+  }
+
+  /// The full name of the repository of the issue this option was created for. Options created
+  /// before the marker had the repository have none.
+  pub fn repository(&self) -> Option<&str> {
+    let (reference, _) = self.description.split_once(ISSUE_MARKER)?;
+    let (repository, _) = reference.trim().rsplit_once('#')?;
+
+    Some(repository).filter(|r| !r.is_empty())
+  }
+
+  /// Whether the issue of this option is in one of the repositories. Options without a
+  /// repository are only in a single repository.
+  pub fn is_in(&self, repositories: &[String]) -> bool {
+    match self.repository() {
+      Some(repository) => repositories
+        .iter()
+        .any(|r| r.eq_ignore_ascii_case(repository)),
+      None => repositories.len() == 1,
+    }
+  }
+
+  pub fn id(&self) -> Option<&str> {
+    self.id.as_deref()
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn color(&self) -> &Color {
+    &self.color
+  }
+
+  pub fn description(&self) -> &str {
+    &self.description
+  }
+}
+
+/// How the epic state of an issue changed with an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+  BecameEpic,
+  StoppedBeingEpic,
+  Unchanged(bool),
+}
+
+impl Transition {
+  /// Whether the issue is an epic after the event.
+  pub fn is_epic(&self) -> bool {
+    matches!(self, Transition::BecameEpic | Transition::Unchanged(true))
+  }
+}
+
+impl Display for Transition {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Transition::BecameEpic => write!(f, "became-epic"),
+      Transition::StoppedBeingEpic => write!(f, "stopped-being-epic"),
+      Transition::Unchanged(_) => write!(f, "unchanged"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+  Created(SingleSelectOption),
+  Renamed(SingleSelectOption),
+  Recolored(SingleSelectOption),
+  Removed(SingleSelectOption),
+  Transferred(SingleSelectOption),
+  Skipped(SingleSelectOption),
+}
+
+impl Operation {
+  pub fn option(&self) -> &SingleSelectOption {
+    match self {
+      Operation::Created(option)
+      | Operation::Renamed(option)
+      | Operation::Recolored(option)
+      | Operation::Removed(option)
+      | Operation::Transferred(option)
+      | Operation::Skipped(option) => option,
+    }
+  }
+}
+
+impl Display for Operation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Operation::Created(_) => write!(f, "created"),
+      Operation::Renamed(_) => write!(f, "renamed"),
+      Operation::Recolored(_) => write!(f, "recolored"),
+      Operation::Removed(_) => write!(f, "removed"),
+      Operation::Transferred(_) => write!(f, "transferred"),
+      Operation::Skipped(_) => write!(f, "skipped"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::str::FromStr;
+
+  use crate::models::{
+    self, Action, ClosePolicy, Color, Issue, Label, OwnerType, SingleSelectField,
+    SingleSelectOption,
+  };
+
+  use crate::errors::Errors;
+  use crate::fixtures::{issue_json, issue_with, params, project_id, try_params};
+
+  use super::{Expression, Project, Transition};
+
+  const ISSUE_EVENT: &str = "{\n  \"action\": \"opened\",\n  \"issue\": {\n    \"active_lock_reason\": null,\n    \"assignee\": null,\n    \"assignees\": [],\n    \"author_association\": \"OWNER\",\n    \"body\": null,\n    \"closed_at\": null,\n    \"comments\": 0,\n    \"comments_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/comments\",\n    \"created_at\": \"2023-03-06T13:14:03Z\",\n    \"events_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/events\",\n    \"html_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/19\",\n    \"id\": 1611401201,\n    \"labels\": [],\n    \"labels_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/labels{/name}\",\n    \"locked\": false,\n    \"milestone\": null,\n    \"node_id\": \"I_kwDOJEXPh85gDAfx\",\n    \"number\": 19,\n    \"performed_via_github_app\": null,\n    \"reactions\": {\n      \"+1\": 0,\n      \"-1\": 0,\n      \"confused\": 0,\n      \"eyes\": 0,\n      \"heart\": 0,\n      \"hooray\": 0,\n      \"laugh\": 0,\n      \"rocket\": 0,\n      \"total_count\": 0,\n      \"url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/reactions\"\n    },\n    \"repository_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project\",\n    \"state\": \"open\",\n    \"state_reason\": null,\n    \"timeline_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19/timeline\",\n    \"title\": \"Test19\",\n    \"updated_at\": \"2023-03-06T13:14:03Z\",\n    \"url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/19\",\n    \"user\": {\n      \"avatar_url\": \"https://avatars.githubusercontent.com/u/33718194?v=4\",\n      \"events_url\": \"https://api.github.com/users/JenSeReal/events{/privacy}\",\n      \"followers_url\": \"https://api.github.com/users/JenSeReal/followers\",\n      \"following_url\": \"https://api.github.com/users/JenSeReal/following{/other_user}\",\n      \"gists_url\": \"https://api.github.com/users/JenSeReal/gists{/gist_id}\",\n      \"gravatar_id\": \"\",\n      \"html_url\": \"https://github.com/JenSeReal\",\n      \"id\": 33718194,\n      \"login\": \"JenSeReal\",\n      \"node_id\": \"MDQ6VXNlcjMzNzE4MTk0\",\n      \"organizations_url\": \"https://api.github.com/users/JenSeReal/orgs\",\n      \"received_events_url\": \"https://api.github.com/users/JenSeReal/received_events\",\n      \"repos_url\": \"https://api.github.com/users/JenSeReal/repos\",\n      \"site_admin\": false,\n      \"starred_url\": \"https://api.github.com/users/JenSeReal/starred{/owner}{/repo}\",\n      \"subscriptions_url\": \"https://api.github.com/users/JenSeReal/subscriptions\",\n      \"type\": \"User\",\n      \"url\": \"https://api.github.com/users/JenSeReal\"\n    }\n  },\n  \"repository\": {\n    \"allow_forking\": true,\n    \"archive_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/{archive_format}{/ref}\",\n    \"archived\": false,\n    \"assignees_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/assignees{/user}\",\n    \"blobs_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/blobs{/sha}\",\n    \"branches_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/branches{/branch}\",\n    \"clone_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project.git\",\n    \"collaborators_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/collaborators{/collaborator}\",\n    \"comments_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/comments{/number}\",\n    \"commits_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/commits{/sha}\",\n    \"compare_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/compare/{base}...{head}\",\n    \"contents_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/contents/{+path}\",\n    \"contributors_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/contributors\",\n    \"created_at\": \"2023-03-02T09:03:40Z\",\n    \"default_branch\": \"main\",\n    \"deployments_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/deployments\",\n    \"description\": null,\n    \"disabled\": false,\n    \"downloads_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/downloads\",\n    \"events_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/events\",\n    \"fork\": false,\n    \"forks\": 0,\n    \"forks_count\": 0,\n    \"forks_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/forks\",\n    \"full_name\": \"JenSeReal/example-add-epic-issue-to-project\",\n    \"git_commits_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/commits{/sha}\",\n    \"git_refs_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/refs{/sha}\",\n    \"git_tags_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/tags{/sha}\",\n    \"git_url\": \"git://github.com/JenSeReal/example-add-epic-issue-to-project.git\",\n    \"has_discussions\": false,\n    \"has_downloads\": true,\n    \"has_issues\": true,\n    \"has_pages\": false,\n    \"has_projects\": true,\n    \"has_wiki\": false,\n    \"homepage\": null,\n    \"hooks_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/hooks\",\n    \"html_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project\",\n    \"id\": 608554887,\n    \"is_template\": false,\n    \"issue_comment_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/comments{/number}\",\n    \"issue_events_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues/events{/number}\",\n    \"issues_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/issues{/number}\",\n    \"keys_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/keys{/key_id}\",\n    \"labels_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/labels{/name}\",\n    \"language\": null,\n    \"languages_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/languages\",\n    \"license\": {\n      \"key\": \"mit\",\n      \"name\": \"MIT License\",\n      \"node_id\": \"MDc6TGljZW5zZTEz\",\n      \"spdx_id\": \"MIT\",\n      \"url\": \"https://api.github.com/licenses/mit\"\n    },\n    \"merges_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/merges\",\n    \"milestones_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/milestones{/number}\",\n    \"mirror_url\": null,\n    \"name\": \"example-add-epic-issue-to-project\",\n    \"node_id\": \"R_kgDOJEXPhw\",\n    \"notifications_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/notifications{?since,all,participating}\",\n    \"open_issues\": 19,\n    \"open_issues_count\": 19,\n    \"owner\": {\n      \"avatar_url\": \"https://avatars.githubusercontent.com/u/33718194?v=4\",\n      \"events_url\": \"https://api.github.com/users/JenSeReal/events{/privacy}\",\n      \"followers_url\": \"https://api.github.com/users/JenSeReal/followers\",\n      \"following_url\": \"https://api.github.com/users/JenSeReal/following{/other_user}\",\n      \"gists_url\": \"https://api.github.com/users/JenSeReal/gists{/gist_id}\",\n      \"gravatar_id\": \"\",\n      \"html_url\": \"https://github.com/JenSeReal\",\n      \"id\": 33718194,\n      \"login\": \"JenSeReal\",\n      \"node_id\": \"MDQ6VXNlcjMzNzE4MTk0\",\n      \"organizations_url\": \"https://api.github.com/users/JenSeReal/orgs\",\n      \"received_events_url\": \"https://api.github.com/users/JenSeReal/received_events\",\n      \"repos_url\": \"https://api.github.com/users/JenSeReal/repos\",\n      \"site_admin\": false,\n      \"starred_url\": \"https://api.github.com/users/JenSeReal/starred{/owner}{/repo}\",\n      \"subscriptions_url\": \"https://api.github.com/users/JenSeReal/subscriptions\",\n      \"type\": \"User\",\n      \"url\": \"https://api.github.com/users/JenSeReal\"\n    },\n    \"private\": true,\n    \"pulls_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/pulls{/number}\",\n    \"pushed_at\": \"2023-03-06T08:52:13Z\",\n    \"releases_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/releases{/id}\",\n    \"size\": 6,\n    \"ssh_url\": \"git@github.com:JenSeReal/example-add-epic-issue-to-project.git\",\n    \"stargazers_count\": 0,\n    \"stargazers_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/stargazers\",\n    \"statuses_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/statuses/{sha}\",\n    \"subscribers_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/subscribers\",\n    \"subscription_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/subscription\",\n    \"svn_url\": \"https://github.com/JenSeReal/example-add-epic-issue-to-project\",\n    \"tags_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/tags\",\n    \"teams_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/teams\",\n    \"topics\": [],\n    \"trees_url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project/git/trees{/sha}\",\n    \"updated_at\": \"2023-03-02T09:03:40Z\",\n    \"url\": \"https://api.github.com/repos/JenSeReal/example-add-epic-issue-to-project\",\n    \"visibility\": \"private\",\n    \"watchers\": 0,\n    \"watchers_count\": 0,\n    \"web_commit_signoff_required\": false\n  },\n  \"sender\": {\n    \"avatar_url\": \"https://avatars.githubusercontent.com/u/33718194?v=4\",\n    \"events_url\": \"https://api.github.com/users/JenSeReal/events{/privacy}\",\n    \"followers_url\": \"https://api.github.com/users/JenSeReal/followers\",\n    \"following_url\": \"https://api.github.com/users/JenSeReal/following{/other_user}\",\n    \"gists_url\": \"https://api.github.com/users/JenSeReal/gists{/gist_id}\",\n    \"gravatar_id\": \"\",\n    \"html_url\": \"https://github.com/JenSeReal\",\n    \"id\": 33718194,\n    \"login\": \"JenSeReal\",\n    \"node_id\": \"MDQ6VXNlcjMzNzE4MTk0\",\n    \"organizations_url\": \"https://api.github.com/users/JenSeReal/orgs\",\n    \"received_events_url\": \"https://api.github.com/users/JenSeReal/received_events\",\n    \"repos_url\": \"https://api.github.com/users/JenSeReal/repos\",\n    \"site_admin\": false,\n    \"starred_url\": \"https://api.github.com/users/JenSeReal/starred{/owner}{/repo}\",\n    \"subscriptions_url\": \"https://api.github.com/users/JenSeReal/subscriptions\",\n    \"type\": \"User\",\n    \"url\": \"https://api.github.com/users/JenSeReal\"\n  }\n}";
+
+  const ISSUE_EDITED_EVENT: &str = r#"{
+    "action": "edited",
+    "changes": { "title": { "from": "Test" } },
+    "issue": {
+      "id": 1611401201,
+      "node_id": "I_kwDOJEXPh85gDAfx",
+      "labels": [],
+      "number": 19,
+      "title": "Test19",
+      "html_url": "https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/19"
+    }
+  }"#;
+
+  const PROJECT_URL: &str = "https://github.com/users/JenSeReal/projects/3";
+
+  const PROJECT_URL_ORG: &str = "https://github.com/orgs/JenSeReal/projects/3";
+
+  #[test]
+  fn test_deserealizazion_issue_event() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+    let labels: Vec<Label> = vec![];
+
+    assert_eq!(event.issue().id(), 1611401201);
+    assert_eq!(event.issue().node_id(), "I_kwDOJEXPh85gDAfx");
+    assert_eq!(event.issue().labels().cloned().collect::<Vec<_>>(), labels);
+    assert_eq!(event.issue().number(), 19);
+    assert_eq!(event.issue().title(), "Test19");
+    assert_eq!(event.action(), &Action::Opened);
+    assert!(event.changes().title().is_none());
+
+    Ok(())
+  }
+
+  #[test]
+  fn deserialize_issue_edited_event() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EDITED_EVENT)?;
+
+    assert_eq!(event.action(), &Action::Edited);
+    assert_eq!(event.changes().title().map(|c| c.from()), Some("Test"));
+    assert!(event.changes().body().is_none());
+
+    Ok(())
+  }
+
+  #[test]
+  fn deserialize_issue_event_actions() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+    assert_eq!(
+      event.repository().map(|r| r.full_name()),
+      Some("JenSeReal/example-add-epic-issue-to-project")
+    );
+    assert_eq!(event.sender().map(|s| s.login()), Some("JenSeReal"));
+
+    let event = |action: &str| -> anyhow::Result<models::IssueEvent, anyhow::Error> {
+      let mut event: serde_json::Value = serde_json::from_str(ISSUE_EDITED_EVENT)?;
+      event["action"] = action.into();
+      Ok(serde_json::from_value(event)?)
+    };
+
+    for action in [
+      Action::Transferred,
+      Action::Pinned,
+      Action::Assigned,
+      Action::Milestoned,
+      Action::Demilestoned,
+      Action::Untyped,
+    ] {
+      assert_eq!(event(&action.to_string())?.action(), &action);
+    }
+    assert_eq!(event("sub_issue_added")?.action(), &Action::Other);
+
+    Ok(())
+  }
+
+  #[test]
+  fn renamed_option_keeps_id_and_color() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EDITED_EVENT)?;
+    let option = SingleSelectOption::new(
+      Some("1".to_string()),
+      "Test".to_string(),
+      Color::Green,
+      String::new(),
+    );
+    let field = SingleSelectField::new(
+      project_id(),
+      "id".to_string(),
+      "Epic".to_string(),
+      vec![option],
+    );
+
+    let option = field
+      .find_option(event.issue(), event.changes().title().unwrap().from())
+      .unwrap()
+      .renamed(event.issue(), &[]);
+
+    assert_eq!(option.id(), Some("1"));
+    assert_eq!(option.name(), "Test19");
+    assert_eq!(option.color(), &Color::Green);
+    assert_eq!(option.issue_id(), Some(1611401201));
+
+    Ok(())
+  }
+
+  #[test]
+  fn option_for_issue_round_trips_issue_id() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+    let option = SingleSelectOption::for_issue(event.issue(), &[]);
+
+    assert_eq!(option.name(), "Test19");
+    assert_eq!(option.issue_id(), Some(1611401201));
+    assert_eq!(option.issue_number(), Some(19));
+    assert_eq!(
+      option.repository(),
+      Some("JenSeReal/example-add-epic-issue-to-project")
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn option_for_issue_names_colliding_titles_like_sync() -> anyhow::Result<(), anyhow::Error> {
+    let mut api = issue_json(1711400003, 3, "test19", &["epic"], "open");
+    api["html_url"] = "https://github.com/JenSeReal/api/issues/3".into();
+    let api: Issue = serde_json::from_value(api)?;
+    let web = issue_with(1611401201, 19, "Test19", &["epic"], "open");
+    let others = [SingleSelectOption::for_issue(&api, &[])];
+
+    let option = SingleSelectOption::for_issue(&web, &others);
+    assert_eq!(option.name(), "example-add-epic-issue-to-project#19 Test19");
+
+    // Renaming on an edited event keeps the name sync gave the option.
+    let renamed = option.renamed(&web, &[others[0].clone(), option.clone()]);
+    assert_eq!(renamed.name(), option.name());
+    assert_eq!(
+      SingleSelectOption::for_issue(&api, &[option]).name(),
+      "api#3 test19"
+    );
+
+    assert_eq!(SingleSelectOption::for_issue(&web, &[]).name(), "Test19");
+    assert!(others[0].is_in(&["jensereal/API".to_string()]));
+    assert!(!others[0].is_in(&["JenSeReal/web".to_string()]));
+
+    Ok(())
+  }
+
+  #[test]
+  fn option_for_matches_by_issue_id() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+    let field = SingleSelectField::new(
+      project_id(),
+      "PVTSSF_epic".to_string(),
+      "Epic".to_string(),
+      vec![
+        SingleSelectOption::new(
+          Some("1".to_string()),
+          "Test19".to_string(),
+          Default::default(),
+          "#7 issue-id:42".to_string(),
+        ),
+        SingleSelectOption::new(
+          Some("2".to_string()),
+          "Renamed".to_string(),
+          Default::default(),
+          "#19 issue-id:1611401201".to_string(),
+        ),
+      ],
+    );
+
+    assert_eq!(
+      field.option_for(event.issue()).and_then(|o| o.id()),
+      Some("2")
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn option_for_matches_legacy_option_by_name() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+    let mut options = vec![SingleSelectOption::new(
+      Some("1".to_string()),
+      "Test19".to_string(),
+      Default::default(),
+      String::new(),
+    )];
+    let field = SingleSelectField::new(
+      project_id(),
+      "id".to_string(),
+      "Epic".to_string(),
+      options.clone(),
+    );
+
+    assert_eq!(
+      field.option_for(event.issue()).and_then(|o| o.id()),
+      Some("1")
+    );
+
+    options[0] = SingleSelectOption::new(
+      Some("1".to_string()),
+      "Test19".to_string(),
+      Default::default(),
+      "#7 issue-id:42".to_string(),
+    );
+    let field = SingleSelectField::new(project_id(), "id".to_string(), "Epic".to_string(), options);
+
+    assert_eq!(field.option_for(event.issue()), None);
+
+    Ok(())
+  }
+
+  #[test]
+  fn parse_close_policy() -> anyhow::Result<(), anyhow::Error> {
+    assert_eq!(ClosePolicy::from_str("keep")?, ClosePolicy::Keep);
+    assert_eq!(
+      ClosePolicy::from_str("rename-with-suffix")?,
+      ClosePolicy::RenameWithSuffix
+    );
+    assert_eq!(ClosePolicy::from_str("recolor")?, ClosePolicy::Recolor);
+    assert_eq!(ClosePolicy::from_str("remove")?, ClosePolicy::Remove);
+    assert!(ClosePolicy::from_str("delete").is_err());
+
+    Ok(())
+  }
+
+  #[test]
+  fn closed_option_is_reverted_when_reopened() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+    let option = SingleSelectOption::for_issue(event.issue(), &[]);
+
+    let renamed = option.closed(&ClosePolicy::RenameWithSuffix);
+    assert_eq!(renamed.name(), "✅ Test19");
+    assert_eq!(renamed.issue_id(), Some(1611401201));
+    assert_eq!(renamed.reopened(&ClosePolicy::RenameWithSuffix), option);
+
+    let recolored = option.closed(&ClosePolicy::Recolor);
+    assert_eq!(recolored.color(), &Color::Purple);
+    assert_eq!(recolored.reopened(&ClosePolicy::Recolor), option);
+
+    assert_eq!(option.closed(&ClosePolicy::Keep), option);
+
+    Ok(())
+  }
+
+  #[test]
+  fn transferred_option_moves_to_the_new_issue() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_value(serde_json::json!({
+      "action": "transferred",
+      "changes": {
+        "new_issue": issue_json(1711400007, 7, "Test19", &[], "open"),
+      },
+      "issue": issue_json(1611401201, 19, "Test19", &[], "open")
+    }))?;
+    let new_issue = event.changes().new_issue().unwrap();
+    let option = SingleSelectOption::new(
+      Some("a1".to_string()),
+      "Roadmap".to_string(),
+      Color::Purple,
+      "#19 issue-id:1611401201 previous-color:RED".to_string(),
+    );
+
+    let moved = option.rekeyed(new_issue);
+
+    assert_eq!(moved.id(), Some("a1"));
+    assert_eq!(moved.name(), "Roadmap");
+    assert_eq!(moved.color(), &Color::Purple);
+    assert_eq!(moved.issue_id(), Some(1711400007));
+    assert_eq!(
+      moved.description(),
+      "JenSeReal/example-add-epic-issue-to-project#7 issue-id:1711400007 title:\"Test19\" \
+       previous-color:RED"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn reopening_keeps_custom_name_and_color() -> anyhow::Result<(), anyhow::Error> {
+    let event: models::IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+    let option = SingleSelectOption::new(
+      Some("a1".to_string()),
+      "Roadmap 2024".to_string(),
+      Color::Red,
+      format!("#19 issue-id:{}", event.issue().id()),
+    );
+
+    let renamed = option.closed(&ClosePolicy::RenameWithSuffix);
+    assert_eq!(renamed.name(), "✅ Roadmap 2024");
+    assert_eq!(renamed.closed(&ClosePolicy::RenameWithSuffix), renamed);
+    assert_eq!(renamed.reopened(&ClosePolicy::RenameWithSuffix), option);
+
+    let recolored = option.closed(&ClosePolicy::Recolor);
+    assert_eq!(recolored.color(), &Color::Purple);
+    assert_eq!(recolored.issue_id(), Some(event.issue().id()));
+    assert_eq!(recolored.closed(&ClosePolicy::Recolor), recolored);
+    assert_eq!(recolored.reopened(&ClosePolicy::Recolor), option);
+
+    let desired = SingleSelectOption::for_issue(event.issue(), &[]).closed(&ClosePolicy::Recolor);
+    assert_eq!(
+      option
+        .updated(&desired)
+        .reopened(&ClosePolicy::Recolor)
+        .color(),
+      &Color::Red
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn deserialize_project_url() -> anyhow::Result<(), anyhow::Error> {
+    let project_url = Project::from_str(PROJECT_URL)?;
+
+    assert_eq!(project_url.url, PROJECT_URL.to_string());
+
+    assert_eq!(project_url.owner_type, OwnerType::Users);
+    assert_eq!(project_url.owner_name, "JenSeReal".to_string());
+    assert_eq!(project_url.number, 3);
+
+    Ok(())
+  }
+
+  #[test]
+  fn deserialize_project_url_org() -> anyhow::Result<(), anyhow::Error> {
+    let project_url = Project::from_str(PROJECT_URL_ORG)?;
+
+    assert_eq!(project_url.url, PROJECT_URL_ORG.to_string());
+
+    assert_eq!(project_url.owner_type, OwnerType::Organizations);
     assert_eq!(project_url.owner_name, "JenSeReal".to_string());
     assert_eq!(project_url.number, 3);
 
     Ok(())
   }
+
+  #[test]
+  fn blank_labels_are_ignored() {
+    let params = params(&[" , ", "AND"]);
+
+    assert!(params.labels().is_empty());
+    assert_eq!(params.filter(), &Expression::Or(vec![]));
+  }
+
+  #[test]
+  fn parse_inputs_case_insensitive() {
+    let params = params(&["epic", "and", "", "Rename-With-Suffix", "TRUE", "False"]);
+
+    assert!(matches!(params.operator(), models::Operator::And));
+    assert_eq!(params.on_close(), &ClosePolicy::RenameWithSuffix);
+    assert_eq!(params.field_name(), "Epic");
+    assert!(params.add_epic_item());
+    assert!(!params.dry_run());
+  }
+
+  #[test]
+  fn report_all_invalid_inputs() {
+    let error = models::Params::try_from(models::Args(
+      [
+        "add-epic-to-project",
+        "https://github.com/JenSeReal/projects/3",
+        "token",
+        "epic, /^epic(/",
+        "XOR",
+        "",
+        "archive",
+        "yes",
+        "",
+        "epic AND",
+      ]
+      .map(String::from)
+      .to_vec(),
+    ))
+    .unwrap_err();
+
+    let Some(Errors::InvalidInputs(inputs)) = error.downcast_ref::<Errors>() else {
+      panic!("Expected invalid inputs, got {error}");
+    };
+    assert_eq!(
+      inputs.iter().map(|(i, _)| i.as_str()).collect::<Vec<_>>(),
+      [
+        "project-url",
+        "labels",
+        "label-operator",
+        "on-close",
+        "add-epic-item",
+        "label-expression"
+      ]
+    );
+    assert_eq!(
+      inputs[2].1,
+      "Could not parse operator XOR. Possible values are AND, OR and NOT"
+    );
+  }
+
+  #[test]
+  fn report_missing_inputs() {
+    let error =
+      models::Params::try_from(models::Args(vec!["add-epic-to-project".to_string()])).unwrap_err();
+
+    assert!(matches!(
+      error.downcast_ref::<Errors>(),
+      Some(Errors::InvalidInputs(inputs)) if inputs.len() == 2
+    ));
+  }
+
+  #[test]
+  fn combine_label_filter_and_criteria() {
+    let params = |labels: &str, mode: &str| {
+      params(&[
+        labels, "", "", "", "", "", "", "[EPIC]*", "", "", "", "", "", mode,
+      ])
+    };
+    let issue = |title: &str, label: &str| issue_with(1611401201, 19, title, &[label], "open");
+
+    let any = params("epic", "any");
+    assert!(any.is_epic(&issue("[EPIC] Roadmap", "bug")));
+    assert!(any.is_epic(&issue("Roadmap", "epic")));
+    assert!(!any.is_epic(&issue("Roadmap", "bug")));
+
+    let all = params("epic", "ALL");
+    assert!(all.is_epic(&issue("[EPIC] Roadmap", "epic")));
+    assert!(!all.is_epic(&issue("[EPIC] Roadmap", "bug")));
+
+    let title_only = params("", "all");
+    assert!(title_only.is_epic(&issue("[EPIC] Roadmap", "bug")));
+  }
+
+  #[test]
+  fn classify_label_transitions() {
+    let params = params(&["epic"]);
+    let event = |action: &str, labels: &[&str], label: &str| -> models::IssueEvent {
+      serde_json::from_value(serde_json::json!({
+        "action": action,
+        "label": { "id": 1, "name": label },
+        "issue": issue_json(1611401201, 19, "Test19", labels, "open")
+      }))
+      .unwrap()
+    };
+
+    assert_eq!(
+      params.transition(&event("labeled", &["epic"], "epic")),
+      Transition::BecameEpic
+    );
+    assert_eq!(
+      params.transition(&event("unlabeled", &[], "epic")),
+      Transition::StoppedBeingEpic
+    );
+    assert_eq!(
+      params.transition(&event("labeled", &["epic", "bug"], "bug")),
+      Transition::Unchanged(true)
+    );
+    assert_eq!(
+      params.transition(&event("unlabeled", &["bug"], "wontfix")),
+      Transition::Unchanged(false)
+    );
+    assert_eq!(
+      params.transition(&event("opened", &[], "epic")),
+      Transition::Unchanged(false)
+    );
+  }
+
+  #[test]
+  fn parse_event_by_name() -> anyhow::Result<(), anyhow::Error> {
+    let pull_request = r#"{
+      "action": "labeled",
+      "number": 7,
+      "label": { "id": 1, "name": "epic" },
+      "pull_request": {
+        "id": 1254639112,
+        "node_id": "PR_kwDOJEXPh85Kyg8I",
+        "labels": [{ "id": 1, "name": "epic" }],
+        "number": 7,
+        "title": "Roadmap",
+        "draft": true,
+        "html_url": "https://github.com/JenSeReal/example-add-epic-issue-to-project/pull/7"
+      }
+    }"#;
+
+    let models::Event::PullRequest(event) = models::Event::parse("pull_request", pull_request)?
+    else {
+      panic!("Expected a pull request event");
+    };
+    let event: models::IssueEvent = event.into();
+    assert_eq!(event.action(), &Action::Labeled);
+    assert_eq!(event.issue().node_id(), "PR_kwDOJEXPh85Kyg8I");
+    assert_eq!(event.label().map(|l| l.name()), Some("epic"));
+
+    assert!(matches!(
+      models::Event::parse("issues", ISSUE_EVENT)?,
+      models::Event::Issues(_)
+    ));
+    assert!(matches!(
+      models::Event::parse("push", "{}")
+        .unwrap_err()
+        .downcast_ref::<Errors>(),
+      Some(Errors::UnsupportedEvent(name)) if name == "push"
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn desired_option_follows_state_and_close_policy() -> anyhow::Result<(), anyhow::Error> {
+    let issue = |state: &str| issue_with(1611401201, 19, "Test19", &[], state);
+
+    assert_eq!(
+      SingleSelectOption::desired(&issue("open"), &ClosePolicy::Remove, &[]),
+      Some(SingleSelectOption::for_issue(&issue("open"), &[]))
+    );
+    assert_eq!(
+      SingleSelectOption::desired(&issue("closed"), &ClosePolicy::Remove, &[]),
+      None
+    );
+    assert_eq!(
+      SingleSelectOption::desired(&issue("closed"), &ClosePolicy::Recolor, &[]).map(|o| *o.color()),
+      Some(Color::Purple)
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn parse_backfill_inputs() -> anyhow::Result<(), anyhow::Error> {
+    let parse = |state: &str, batch_size: &str, start_page: &str| {
+      let mut inputs = vec!["epic"];
+      inputs.resize(15, "");
+      inputs.extend([state, batch_size, start_page]);
+      try_params(&inputs)
+    };
+
+    let params = parse("", "", "")?;
+    assert_eq!(params.state(), &models::StateFilter::All);
+    assert_eq!(params.batch_size(), 50);
+    assert_eq!(params.start_page(), 1);
+
+    let params = parse("Closed", "10", "4")?;
+    assert_eq!(params.state(), &models::StateFilter::Closed);
+    assert_eq!(params.batch_size(), 10);
+    assert_eq!(params.start_page(), 4);
+
+    let error = parse("merged", "0", "-1").unwrap_err();
+    assert!(matches!(
+      error.downcast_ref::<Errors>(),
+      Some(Errors::InvalidInputs(inputs)) if inputs.len() == 3
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn parse_repository_inputs() -> anyhow::Result<(), anyhow::Error> {
+    let parse = |repositories: &str, topic: &str| {
+      let mut inputs = vec!["epic"];
+      inputs.resize(20, "");
+      inputs.extend([repositories, topic]);
+      try_params(&inputs)
+    };
+
+    let params = parse("", "")?;
+    assert!(params.repositories().is_empty());
+    assert_eq!(params.topic(), None);
+
+    let params = parse("web, JenSeReal/api.rs,", "roadmap")?;
+    assert_eq!(params.repositories(), &vec!["web", "JenSeReal/api.rs"]);
+    assert_eq!(params.topic(), Some("roadmap"));
+
+    let error = parse("web, a/b/c, /api", "").unwrap_err();
+    assert!(matches!(
+      error.downcast_ref::<Errors>(),
+      Some(Errors::InvalidInputs(inputs)) if inputs.len() == 2
+    ));
+
+    Ok(())
+  }
 }
@@ -0,0 +1,535 @@
+use anyhow::Context;
+
+use crate::models::{ClosePolicy, Issue, Operation, SingleSelectField, SingleSelectOption};
+use crate::queries::{self, Client};
+
+/// Adds an option for the issue to the field, unless the field already has one.
+pub async fn add(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  if let Some(option) = field.option_for(issue) {
+    return Ok(Operation::Skipped(option.clone()));
+  }
+
+  create(
+    client,
+    field,
+    issue,
+    SingleSelectOption::for_issue(issue, field.options()),
+  )
+  .await
+}
+
+/// Renames the option of the issue in place after its title was edited. Project items that are
+/// assigned to it stay assigned.
+pub async fn rename(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+  previous_title: &str,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let Some(current) = field.find_option(issue, previous_title) else {
+    return add(client, field, issue).await;
+  };
+
+  update(
+    client,
+    field,
+    issue,
+    current,
+    current.renamed(issue, field.options()),
+    Operation::Renamed,
+  )
+  .await
+}
+
+/// Appends new options to the field with one update and returns the updated field.
+pub async fn add_all(
+  client: &Client,
+  field: &SingleSelectField,
+  new: &[SingleSelectOption],
+) -> anyhow::Result<SingleSelectField, anyhow::Error> {
+  let mut options = field.options().clone();
+  options.extend_from_slice(new);
+
+  queries::update_single_select_options(client, field, &options, &[]).await
+}
+
+/// Renames the option of the issue to a custom name, adding the option if it is missing.
+pub async fn rename_to(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+  name: &str,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let Some(current) = field.option_for(issue) else {
+    return create(
+      client,
+      field,
+      issue,
+      SingleSelectOption::for_issue(issue, field.options()).named(name),
+    )
+    .await;
+  };
+
+  update(
+    client,
+    field,
+    issue,
+    current,
+    current.named(name),
+    Operation::Renamed,
+  )
+  .await
+}
+
+/// Applies the close policy to the option of the issue, when it was closed or stopped being an
+/// epic.
+pub async fn close(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+  policy: &ClosePolicy,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let Some(current) = field.option_for(issue) else {
+    return Ok(Operation::Skipped(SingleSelectOption::for_issue(
+      issue,
+      field.options(),
+    )));
+  };
+
+  match policy {
+    ClosePolicy::Keep => Ok(Operation::Skipped(current.clone())),
+    ClosePolicy::RenameWithSuffix => {
+      let closed = current.closed(policy);
+      update(client, field, issue, current, closed, Operation::Renamed).await
+    }
+    ClosePolicy::Recolor => {
+      let closed = current.closed(policy);
+      update(client, field, issue, current, closed, Operation::Recolored).await
+    }
+    ClosePolicy::Remove => remove(client, field, issue).await,
+  }
+}
+
+/// Reverts the close policy after the issue was reopened or became an epic again, adding the
+/// option if it is missing.
+pub async fn reopen(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+  policy: &ClosePolicy,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let Some(current) = field.option_for(issue) else {
+    return add(client, field, issue).await;
+  };
+
+  match policy {
+    ClosePolicy::Keep | ClosePolicy::Remove => Ok(Operation::Skipped(current.clone())),
+    ClosePolicy::RenameWithSuffix => {
+      let reopened = current.reopened(policy);
+      update(client, field, issue, current, reopened, Operation::Renamed).await
+    }
+    ClosePolicy::Recolor => {
+      let reopened = current.reopened(policy);
+      update(
+        client,
+        field,
+        issue,
+        current,
+        reopened,
+        Operation::Recolored,
+      )
+      .await
+    }
+  }
+}
+
+/// Removes the option of the issue from the field.
+pub async fn remove(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let Some(current) = field.option_for(issue) else {
+    return Ok(Operation::Skipped(SingleSelectOption::for_issue(
+      issue,
+      field.options(),
+    )));
+  };
+
+  let options = field
+    .options()
+    .iter()
+    .filter(|o| o != &current)
+    .cloned()
+    .collect::<Vec<_>>();
+
+  if options.is_empty() {
+    anyhow::bail!(
+      "Could not remove option \"{}\", GitHub requires field \"{}\" to keep at least one option",
+      current.name(),
+      field.name()
+    );
+  }
+
+  queries::update_single_select_options(client, field, &options, std::slice::from_ref(current))
+    .await?;
+
+  Ok(Operation::Removed(current.clone()))
+}
+
+/// Moves the option of a transferred issue to its new issue in place. Project items that are
+/// assigned to it stay assigned.
+pub async fn transfer(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+  new_issue: &Issue,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let Some(current) = field.option_for(issue) else {
+    return Ok(Operation::Skipped(SingleSelectOption::for_issue(
+      new_issue,
+      field.options(),
+    )));
+  };
+
+  update(
+    client,
+    field,
+    new_issue,
+    current,
+    current.rekeyed(new_issue),
+    Operation::Transferred,
+  )
+  .await
+}
+
+/// Appends `option` to the options of the field.
+async fn create(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+  option: SingleSelectOption,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let mut options = field.options().clone();
+  options.push(option);
+
+  let field = queries::update_single_select_options(client, field, &options, &[]).await?;
+
+  written_option(&field, issue).map(Operation::Created)
+}
+
+/// Replaces `current` with `updated` in the options of the field. Options created before the
+/// issue marker are matched by their name, which the update may change, so they get the marker.
+async fn update(
+  client: &Client,
+  field: &SingleSelectField,
+  issue: &Issue,
+  current: &SingleSelectOption,
+  updated: SingleSelectOption,
+  operation: fn(SingleSelectOption) -> Operation,
+) -> anyhow::Result<Operation, anyhow::Error> {
+  let updated = match updated.issue_id() {
+    Some(_) => updated,
+    None => updated.rekeyed(issue),
+  };
+  if &updated == current {
+    return Ok(Operation::Skipped(updated));
+  }
+
+  let options = field
+    .options()
+    .iter()
+    .map(|o| {
+      if o == current {
+        updated.clone()
+      } else {
+        o.clone()
+      }
+    })
+    .collect::<Vec<_>>();
+
+  let field = queries::update_single_select_options(client, field, &options, &[]).await?;
+
+  written_option(&field, issue).map(operation)
+}
+
+fn written_option(
+  field: &SingleSelectField,
+  issue: &Issue,
+) -> anyhow::Result<SingleSelectOption, anyhow::Error> {
+  field.option_for(issue).cloned().context(format!(
+    "GitHub did not return the option for issue #{}",
+    issue.number()
+  ))
+}
+
+#[cfg(test)]
+mod test {
+  use crate::errors::Errors;
+  use crate::fake_github::FakeGitHub;
+  use crate::fixtures::{field, issue, option};
+  use crate::models::{ClosePolicy, Color, Issue, Operation, SingleSelectOption};
+
+  fn existing() -> Vec<SingleSelectOption> {
+    vec![
+      option(Some("a1"), "No epic", Color::Red, None),
+      option(Some("b2"), "Roadmap", Color::Purple, Some((1611400001, 1))),
+      option(Some("c3"), "Search", Color::Gray, Some((1611400002, 2))),
+    ]
+  }
+
+  fn epic() -> Issue {
+    issue(&["epic"])
+  }
+
+  fn input(option: &SingleSelectOption) -> serde_json::Value {
+    serde_json::json!({
+      "name": option.name(),
+      "color": option.color(),
+      "description": option.description(),
+    })
+  }
+
+  /// The options as GitHub recreates them, all with new ids.
+  fn recreated(options: &[SingleSelectOption]) -> Vec<SingleSelectOption> {
+    options
+      .iter()
+      .enumerate()
+      .map(|(i, o)| {
+        SingleSelectOption::new(
+          Some(format!("new{i}")),
+          o.name().to_string(),
+          *o.color(),
+          o.description().to_string(),
+        )
+      })
+      .collect()
+  }
+
+  /// The items of the project, with the ids of their items and options.
+  fn items(items: &[(&str, &str)]) -> serde_json::Value {
+    let nodes = items
+      .iter()
+      .map(|(id, option_id)| {
+        serde_json::json!({
+          "id": id,
+          "content": { "__typename": "DraftIssue", "title": id },
+          "fieldValueByName": {
+            "__typename": "ProjectV2ItemFieldSingleSelectValue",
+            "optionId": option_id,
+            "name": option_id
+          }
+        })
+      })
+      .collect::<Vec<_>>();
+
+    serde_json::json!({
+      "data": {
+        "node": {
+          "__typename": "ProjectV2",
+          "items": { "pageInfo": { "hasNextPage": false, "endCursor": null }, "nodes": nodes }
+        }
+      }
+    })
+  }
+
+  /// The response of GitHub after setting the value of the item.
+  fn restored(item_id: &str) -> serde_json::Value {
+    serde_json::json!({
+      "data": { "updateProjectV2ItemFieldValue": { "projectV2Item": { "id": item_id } } }
+    })
+  }
+
+  /// The response of GitHub after the update.
+  fn response(options: &[SingleSelectOption]) -> serde_json::Value {
+    let options = recreated(options)
+      .iter()
+      .map(|o| {
+        let mut option = input(o);
+        option["id"] = o.id().into();
+        option
+      })
+      .collect::<Vec<_>>();
+
+    serde_json::json!({
+      "data": {
+        "updateProjectV2Field": {
+          "projectV2Field": {
+            "__typename": "ProjectV2SingleSelectField",
+            "id": "PVTSSF_epic",
+            "name": "Epic",
+            "options": options
+          }
+        }
+      }
+    })
+  }
+
+  #[tokio::test]
+  async fn add_round_trips_existing_options() -> anyhow::Result<(), anyhow::Error> {
+    let field = field(existing());
+    let mut expected = existing();
+    expected.push(SingleSelectOption::for_issue(&epic(), &[]));
+    let github = FakeGitHub::start(vec![items(&[]), response(&expected)]).await?;
+
+    let operation = super::add(&github.client()?, &field, &epic()).await?;
+
+    let requests = github.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+      requests[1]["variables"]["options"],
+      serde_json::Value::Array(expected.iter().map(input).collect())
+    );
+    assert!(matches!(operation, Operation::Created(o) if o.id() == Some("new3")));
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn rename_modifies_only_the_option_of_the_issue() -> anyhow::Result<(), anyhow::Error> {
+    let mut options = existing();
+    options.push(option(
+      Some("d4"),
+      "Test",
+      Color::Green,
+      Some((1611401201, 19)),
+    ));
+    let field = field(options);
+    let mut expected = field.options().clone();
+    expected[3] = expected[3].renamed(&epic(), &[]);
+    let github = FakeGitHub::start(vec![items(&[]), response(&expected)]).await?;
+
+    super::rename(&github.client()?, &field, &epic(), "Test").await?;
+
+    assert_eq!(
+      github.requests()[1]["variables"]["options"],
+      serde_json::Value::Array(expected.iter().map(input).collect())
+    );
+    assert_eq!(expected[3].id(), Some("d4"));
+    assert_eq!(expected[3].color(), &Color::Green);
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn restore_items_of_recreated_options() -> anyhow::Result<(), anyhow::Error> {
+    let field = field(existing());
+    let mut expected = existing();
+    expected.push(SingleSelectOption::for_issue(&epic(), &[]));
+    let github = FakeGitHub::start(vec![
+      items(&[("PVTI_1", "b2"), ("PVTI_2", "c3"), ("PVTI_3", "b2")]),
+      response(&expected),
+      restored("PVTI_1"),
+      restored("PVTI_2"),
+      restored("PVTI_3"),
+    ])
+    .await?;
+
+    super::add(&github.client()?, &field, &epic()).await?;
+
+    let requests = github.requests();
+    assert_eq!(requests[0]["variables"]["fieldName"], "Epic");
+    assert_eq!(
+      requests[2..]
+        .iter()
+        .map(|r| (&r["variables"]["itemId"], &r["variables"]["optionId"]))
+        .collect::<Vec<_>>(),
+      [
+        (&"PVTI_1".into(), &"new1".into()),
+        (&"PVTI_2".into(), &"new2".into()),
+        (&"PVTI_3".into(), &"new1".into()),
+      ]
+    );
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn fail_when_github_drops_existing_options() -> anyhow::Result<(), anyhow::Error> {
+    let field = field(existing());
+    let mut recreated = existing()[1..].to_vec();
+    recreated.push(SingleSelectOption::for_issue(&epic(), &[]));
+    let github = FakeGitHub::start(vec![items(&[]), response(&recreated)]).await?;
+
+    let error = super::add(&github.client()?, &field, &epic())
+      .await
+      .unwrap_err();
+
+    assert!(matches!(
+      error.downcast_ref::<Errors>(),
+      Some(Errors::OptionsDropped(_, dropped)) if dropped == &vec!["No epic".to_string()]
+    ));
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn close_legacy_option_adds_the_marker() -> anyhow::Result<(), anyhow::Error> {
+    let mut options = existing();
+    options.push(option(Some("d4"), "Test19", Color::Green, None));
+    let field = field(options);
+    let mut expected = field.options().clone();
+    expected[3] = SingleSelectOption::new(
+      Some("d4".to_string()),
+      "✅ Test19".to_string(),
+      Color::Green,
+      SingleSelectOption::for_issue(&epic(), &[])
+        .description()
+        .to_string(),
+    );
+    let github = FakeGitHub::start(vec![items(&[]), response(&expected)]).await?;
+
+    let operation = super::close(
+      &github.client()?,
+      &field,
+      &epic(),
+      &ClosePolicy::RenameWithSuffix,
+    )
+    .await?;
+
+    assert_eq!(
+      github.requests()[1]["variables"]["options"],
+      serde_json::Value::Array(expected.iter().map(input).collect())
+    );
+    assert_eq!(
+      operation,
+      Operation::Renamed(recreated(&expected)[3].clone())
+    );
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn rename_legacy_option_adds_the_marker() -> anyhow::Result<(), anyhow::Error> {
+    let mut options = existing();
+    options.push(option(Some("d4"), "Test19", Color::Green, None));
+    let field = field(options);
+    let mut expected = field.options().clone();
+    expected[3] = SingleSelectOption::new(
+      Some("d4".to_string()),
+      "Roadmap 2024".to_string(),
+      Color::Green,
+      SingleSelectOption::for_issue(&epic(), &[])
+        .description()
+        .to_string(),
+    );
+    let github = FakeGitHub::start(vec![items(&[]), response(&expected)]).await?;
+
+    let operation = super::rename_to(&github.client()?, &field, &epic(), "Roadmap 2024").await?;
+
+    assert_eq!(
+      github.requests()[1]["variables"]["options"],
+      serde_json::Value::Array(expected.iter().map(input).collect())
+    );
+    assert_eq!(
+      operation,
+      Operation::Renamed(recreated(&expected)[3].clone())
+    );
+
+    Ok(())
+  }
+}
@@ -0,0 +1,102 @@
+use std::{env, fs::OpenOptions, io::Write};
+
+const DELIMITER: &str = "ghadelimiter";
+
+/// Step outputs of the action, written to the file in `GITHUB_OUTPUT`.
+#[derive(Debug, Default)]
+pub struct Outputs {
+  values: Vec<(&'static str, String)>,
+}
+
+impl Outputs {
+  pub fn set(&mut self, name: &'static str, value: impl ToString) -> &mut Self {
+    let value = value.to_string();
+    match self.values.iter_mut().find(|(n, _)| *n == name) {
+      Some((_, v)) => *v = value,
+      None => self.values.push((name, value)),
+    }
+    self
+  }
+
+  /// Appends the outputs to `GITHUB_OUTPUT`. Does nothing when not running in GitHub Actions.
+  pub fn write(&self) -> anyhow::Result<(), anyhow::Error> {
+    let Ok(path) = env::var("GITHUB_OUTPUT") else {
+      return Ok(());
+    };
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(self.encode().as_bytes())?;
+
+    Ok(())
+  }
+
+  /// Encodes every value with a delimiter, so values spanning multiple lines can't inject other
+  /// outputs.
+  fn encode(&self) -> String {
+    self
+      .values
+      .iter()
+      .map(|(name, value)| {
+        let delimiter = (0..)
+          .map(|i| format!("{DELIMITER}_{i}"))
+          .find(|d| !value.contains(d.as_str()))
+          .unwrap_or_default();
+
+        format!("{name}<<{delimiter}\n{value}\n{delimiter}\n")
+      })
+      .collect()
+  }
+}
+
+/// Formats an `::error` workflow command, which GitHub shows as an annotation of the run.
+pub fn error(title: &str, message: &str) -> String {
+  let escape = |s: &str| {
+    s.replace('%', "%25")
+      .replace('\r', "%0D")
+      .replace('\n', "%0A")
+  };
+
+  format!(
+    "::error title={}::{}",
+    escape(title).replace(':', "%3A").replace(',', "%2C"),
+    escape(message)
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use super::Outputs;
+
+  #[test]
+  fn encode_outputs() {
+    let mut outputs = Outputs::default();
+    outputs
+      .set("is-epic", true)
+      .set("option-name", "Test19")
+      .set("is-epic", false);
+
+    assert_eq!(
+      outputs.encode(),
+      "is-epic<<ghadelimiter_0\nfalse\nghadelimiter_0\noption-name<<ghadelimiter_0\nTest19\nghadelimiter_0\n"
+    );
+  }
+
+  #[test]
+  fn encode_multi_line_values() {
+    let mut outputs = Outputs::default();
+    outputs.set("option-name", "Test\nghadelimiter_0\noperation=removed");
+
+    assert_eq!(
+      outputs.encode(),
+      "option-name<<ghadelimiter_1\nTest\nghadelimiter_0\noperation=removed\nghadelimiter_1\n"
+    );
+  }
+
+  #[test]
+  fn escape_error_annotation() {
+    assert_eq!(
+      super::error("Invalid input: labels, operator", "100% wrong\nsecond line"),
+      "::error title=Invalid input%3A labels%2C operator::100%25 wrong%0Asecond line"
+    );
+  }
+}
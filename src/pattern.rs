@@ -0,0 +1,155 @@
+use std::{fmt::Display, str::FromStr};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::errors;
+
+/// A pattern matching label names. Plain names match exactly, names containing `*` or `?` are
+/// globs and `/.../flags` is a regex, e.g. `/^epic(-.*)?$/i`.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+  Exact(String),
+  Glob(String, Regex),
+  Regex(String, Regex),
+}
+
+impl Pattern {
+  pub fn matches(&self, label: &str) -> bool {
+    match self {
+      Pattern::Exact(name) => name == label,
+      Pattern::Glob(_, regex) | Pattern::Regex(_, regex) => regex.is_match(label),
+    }
+  }
+
+  pub fn source(&self) -> &str {
+    match self {
+      Pattern::Exact(source) | Pattern::Glob(source, _) | Pattern::Regex(source, _) => source,
+    }
+  }
+
+  pub fn is_regex(&self) -> bool {
+    matches!(self, Pattern::Regex(_, _))
+  }
+
+  fn glob(glob: &str) -> anyhow::Result<Self, anyhow::Error> {
+    let regex = glob
+      .chars()
+      .map(|c| match c {
+        '*' => ".*".to_string(),
+        '?' => ".".to_string(),
+        c => regex::escape(&c.to_string()),
+      })
+      .collect::<String>();
+
+    Ok(Pattern::Glob(
+      glob.to_string(),
+      Regex::new(&format!("^{regex}$"))?,
+    ))
+  }
+
+  fn regex(source: &str) -> anyhow::Result<Self, anyhow::Error> {
+    let invalid = |reason: String| errors::Errors::InvalidPattern(source.to_string(), reason);
+
+    let (regex, flags) = source[1..]
+      .rsplit_once('/')
+      .ok_or_else(|| invalid("missing closing /".to_string()))?;
+
+    let mut builder = RegexBuilder::new(regex);
+    for flag in flags.chars() {
+      match flag {
+        'i' => builder.case_insensitive(true),
+        'm' => builder.multi_line(true),
+        's' => builder.dot_matches_new_line(true),
+        'x' => builder.ignore_whitespace(true),
+        flag => return Err(invalid(format!("unknown flag {flag}")).into()),
+      };
+    }
+
+    let regex = builder.build().map_err(|e| invalid(e.to_string()))?;
+
+    Ok(Pattern::Regex(source.to_string(), regex))
+  }
+}
+
+impl FromStr for Pattern {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.starts_with('/') {
+      Self::regex(s)
+    } else if s.contains(['*', '?']) {
+      Self::glob(s)
+    } else {
+      Ok(Pattern::Exact(s.to_string()))
+    }
+  }
+}
+
+impl Display for Pattern {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.source())
+  }
+}
+
+impl PartialEq for Pattern {
+  fn eq(&self, other: &Self) -> bool {
+    self.is_regex() == other.is_regex() && self.source() == other.source()
+  }
+}
+
+impl Eq for Pattern {}
+
+#[cfg(test)]
+mod test {
+  use std::str::FromStr;
+
+  use crate::errors::Errors;
+
+  use super::Pattern;
+
+  #[test]
+  fn match_exact() -> anyhow::Result<(), anyhow::Error> {
+    let pattern = Pattern::from_str("Epic 🚀")?;
+
+    assert!(pattern.matches("Epic 🚀"));
+    assert!(!pattern.matches("epic 🚀"));
+
+    Ok(())
+  }
+
+  #[test]
+  fn match_glob() -> anyhow::Result<(), anyhow::Error> {
+    let pattern = Pattern::from_str("epic:*")?;
+
+    assert!(pattern.matches("epic:"));
+    assert!(pattern.matches("epic:roadmap"));
+    assert!(!pattern.matches("type/epic"));
+    assert!(Pattern::from_str("type/ep?c")?.matches("type/epic"));
+    assert!(Pattern::from_str("a.b*")?.matches("a.bc"));
+    assert!(!Pattern::from_str("a.b*")?.matches("axbc"));
+
+    Ok(())
+  }
+
+  #[test]
+  fn match_regex() -> anyhow::Result<(), anyhow::Error> {
+    let pattern = Pattern::from_str("/^epic(-.*)?$/i")?;
+
+    assert!(pattern.matches("epic"));
+    assert!(pattern.matches("Epic-roadmap"));
+    assert!(!pattern.matches("epics"));
+
+    Ok(())
+  }
+
+  #[test]
+  fn invalid_regex() {
+    for pattern in ["/^epic(/", "/^epic$/g", "/^epic$"] {
+      let error = Pattern::from_str(pattern).unwrap_err();
+      assert!(matches!(
+        error.downcast_ref::<Errors>(),
+        Some(Errors::InvalidPattern(source, _)) if source == pattern
+      ));
+    }
+  }
+}
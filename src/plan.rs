@@ -0,0 +1,266 @@
+use std::{fmt::Write as _, fs};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors;
+use crate::models::{Params, ProjectId, SingleSelectField, SingleSelectOption};
+use crate::output::Outputs;
+use crate::queries::Client;
+use crate::reconcile::{self, Change, Diff};
+use crate::summary::Summary;
+use crate::{field, project, repositories};
+
+/// A reconciliation diff written by the `plan` mode, to be reviewed and executed by the `apply`
+/// mode.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+  project_id: String,
+  field_id: String,
+  field_name: String,
+  /// Fingerprints of the options of the field when the plan was made.
+  fingerprints: Vec<String>,
+  diff: Diff,
+}
+
+impl Plan {
+  pub fn new(project_id: &ProjectId, field: &SingleSelectField, diff: Diff) -> Self {
+    Self {
+      project_id: project_id.to_string(),
+      field_id: field.id().to_string(),
+      field_name: field.name().to_string(),
+      fingerprints: field.options().iter().map(fingerprint).collect(),
+      diff,
+    }
+  }
+
+  pub fn diff(&self) -> &Diff {
+    &self.diff
+  }
+
+  /// Fails when the field is not the one of the plan or its options changed since the plan was
+  /// made.
+  pub fn check(
+    &self,
+    project_id: &ProjectId,
+    field: &SingleSelectField,
+  ) -> anyhow::Result<(), anyhow::Error> {
+    let stale = |reason: String| Err(errors::Errors::StalePlan(reason).into());
+
+    if project_id.as_str() != self.project_id || field.id() != self.field_id {
+      return stale(format!(
+        "it was made for field {} of project {}, not field {} of project {}",
+        self.field_id,
+        self.project_id,
+        field.id(),
+        project_id
+      ));
+    }
+
+    let fingerprints = field.options().iter().map(fingerprint).collect::<Vec<_>>();
+    if fingerprints != self.fingerprints {
+      return stale(format!(
+        "the options of field \"{}\" changed since it was made",
+        field.name()
+      ));
+    }
+
+    Ok(())
+  }
+
+  /// The changes of the plan as a Markdown table.
+  pub fn table(&self) -> String {
+    let mut table = String::from("| # | Change | Option | Details |\n| --- | --- | --- | --- |\n");
+
+    for (i, change) in self.diff.changes().iter().enumerate() {
+      let (kind, option, details) = match change {
+        Change::Add { option } => ("add", option.name().to_string(), option.color().to_string()),
+        Change::Rename { from, to } => ("rename", from.name().to_string(), format!("to \"{to}\"")),
+        Change::Recolor { from, to } => (
+          "recolor",
+          from.name().to_string(),
+          format!("{} to {to}", from.color()),
+        ),
+        Change::Remove { option } => ("remove", option.name().to_string(), String::new()),
+        Change::Reorder { order } => ("reorder", String::new(), order.join(", ")),
+      };
+
+      let _ = writeln!(
+        table,
+        "| {} | {kind} | {} | {} |",
+        i + 1,
+        option.replace('|', "\\|"),
+        details.replace('|', "\\|")
+      );
+    }
+
+    table
+  }
+}
+
+/// A fingerprint of everything about an option the API lets us change.
+fn fingerprint(option: &SingleSelectOption) -> String {
+  let digest = Sha256::new()
+    .chain_update(option.id().unwrap_or_default())
+    .chain_update([0])
+    .chain_update(option.name())
+    .chain_update([0])
+    .chain_update(option.color().to_string())
+    .chain_update([0])
+    .chain_update(option.description())
+    .finalize();
+
+  digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Writes the reconciliation diff of the repositories to the plan file.
+pub async fn plan(
+  params: &Params,
+  client: &Client,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  let repositories = repositories::resolve(params, client).await?;
+
+  let project_id = project::resolve(client, params.project()).await?;
+  outputs.set("project-id", &project_id);
+  summary.project(params, &project_id);
+
+  let field = field::find(client, &project_id, params.field_name()).await?;
+  outputs.set("field-id", field.id());
+  summary.field(&field);
+
+  let diff = reconcile::desired_diff(params, client, &repositories, &field).await?;
+  let plan = Plan::new(&project_id, &field, diff);
+  fs::write(params.plan_file(), serde_json::to_string_pretty(&plan)?)?;
+
+  println!("{}", plan.table());
+  println!(
+    "Wrote a plan with {} changes to {}",
+    plan.diff().changes().len(),
+    params.plan_file()
+  );
+  outputs
+    .set("changes", plan.diff().changes().len())
+    .set("plan-file", params.plan_file())
+    .set("plan-table", plan.table());
+  summary.plan(&plan);
+
+  Ok(())
+}
+
+/// Executes the plan file, unless the field changed since the plan was made.
+pub async fn apply(
+  params: &Params,
+  client: &Client,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  let plan: Plan = serde_json::from_str(
+    &fs::read_to_string(params.plan_file())
+      .context(format!("Could not read plan {}", params.plan_file()))?,
+  )?;
+  summary.plan(&plan);
+
+  let project_id = project::resolve(client, params.project()).await?;
+  outputs.set("project-id", &project_id);
+  summary.project(params, &project_id);
+
+  let field = field::find(client, &project_id, params.field_name()).await?;
+  outputs.set("field-id", field.id());
+  summary.field(&field);
+
+  plan.check(&project_id, &field)?;
+  outputs.set("changes", plan.diff().changes().len());
+
+  if plan.diff().is_empty() {
+    println!("The plan has no changes");
+    return Ok(());
+  }
+
+  reconcile::apply(client, &field, plan.diff()).await?;
+  println!(
+    "Applied {} changes of {} to field \"{}\"",
+    plan.diff().changes().len(),
+    params.plan_file(),
+    field.name()
+  );
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use std::collections::HashSet;
+
+  use crate::errors::Errors;
+  use crate::fixtures::{field, option};
+  use crate::models::{Color, ProjectId};
+  use crate::reconcile::Diff;
+
+  use super::Plan;
+
+  #[test]
+  fn plan_round_trips_and_renders_table() -> anyhow::Result<(), anyhow::Error> {
+    let project_id = ProjectId::new("PVT_kwDOA".to_string());
+    let actual = field(vec![
+      option(Some("a"), "Roadmap", Color::Gray, Some((101, 1))),
+      option(Some("b"), "Gone", Color::Gray, Some((102, 2))),
+    ]);
+    let desired = vec![option(None, "Roadmap | 2024", Color::Gray, Some((101, 1)))];
+    let plan = Plan::new(
+      &project_id,
+      &actual,
+      Diff::compute(actual.options(), &desired, &HashSet::from([102])),
+    );
+
+    let parsed: Plan = serde_json::from_str(&serde_json::to_string(&plan)?)?;
+    assert_eq!(parsed, plan);
+    assert_eq!(
+      plan.table(),
+      "| # | Change | Option | Details |
+| --- | --- | --- | --- |
+| 1 | rename | Roadmap | to \"Roadmap \\| 2024\" |
+| 2 | remove | Gone |  |
+"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn refuse_stale_plan() -> anyhow::Result<(), anyhow::Error> {
+    let project_id = ProjectId::new("PVT_kwDOA".to_string());
+    let actual = field(vec![option(
+      Some("a"),
+      "Roadmap",
+      Color::Gray,
+      Some((101, 1)),
+    )]);
+    let plan = Plan::new(
+      &project_id,
+      &actual,
+      Diff::compute(actual.options(), &[], &HashSet::new()),
+    );
+
+    plan.check(&project_id, &actual)?;
+
+    let edited = field(vec![option(
+      Some("a"),
+      "Roadmap (edited)",
+      Color::Gray,
+      Some((101, 1)),
+    )]);
+    let error = plan.check(&project_id, &edited).unwrap_err();
+    assert!(matches!(
+      error.downcast_ref::<Errors>(),
+      Some(Errors::StalePlan(_))
+    ));
+
+    let other = ProjectId::new("PVT_other".to_string());
+    assert!(plan.check(&other, &actual).is_err());
+
+    Ok(())
+  }
+}
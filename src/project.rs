@@ -0,0 +1,203 @@
+use graphql_client::{Error, PathFragment};
+
+use crate::errors;
+use crate::models::{OwnerType, Project, ProjectId};
+use crate::queries::{
+  self, get_project_org, get_project_user, Client, GetProjectOrg, GetProjectUser,
+};
+
+const ACCESS_ERRORS: [&str; 3] = ["not accessible", "required scopes", "permission"];
+
+/// Looks up the node ID of the ProjectV2 behind the project url, using the user or organization
+/// query depending on who owns the project.
+pub async fn resolve(
+  client: &Client,
+  project: &Project,
+) -> anyhow::Result<ProjectId, anyhow::Error> {
+  let (owner, errors) = match project.owner_type() {
+    OwnerType::Users => {
+      let response = queries::send::<GetProjectUser>(
+        client,
+        get_project_user::Variables {
+          project_owner_name: project.owner_name().to_string(),
+          project_number: project.number(),
+        },
+      )
+      .await?;
+
+      (
+        response
+          .data
+          .and_then(|data| data.user)
+          .map(|user| user.project_v2.map(|project| project.id)),
+        response.errors.unwrap_or_default(),
+      )
+    }
+    OwnerType::Organizations => {
+      let response = queries::send::<GetProjectOrg>(
+        client,
+        get_project_org::Variables {
+          project_owner_name: project.owner_name().to_string(),
+          project_number: project.number(),
+        },
+      )
+      .await?;
+
+      (
+        response
+          .data
+          .and_then(|data| data.organization)
+          .map(|org| org.project_v2.map(|project| project.id)),
+        response.errors.unwrap_or_default(),
+      )
+    }
+  };
+
+  classify(project, owner, &errors)
+}
+
+/// Turns the owner lookup into a project id. The outer option is the owner, the inner one the
+/// project of that owner.
+fn classify(
+  project: &Project,
+  owner: Option<Option<String>>,
+  errors: &[Error],
+) -> anyhow::Result<ProjectId, anyhow::Error> {
+  if let Some(error) = errors.iter().find(|e| is_access_error(e)) {
+    return Err(
+      errors::Errors::NoProjectAccess(
+        project.owner_name().to_string(),
+        project.number(),
+        error.message.clone(),
+      )
+      .into(),
+    );
+  }
+
+  match owner {
+    Some(Some(id)) => Ok(ProjectId::new(id)),
+    Some(None) => Err(
+      errors::Errors::ProjectNotFound(project.owner_name().to_string(), project.number()).into(),
+    ),
+    None if errors.is_empty() || errors.iter().any(|e| path_len(e) == 1) => Err(
+      errors::Errors::OwnerNotFound(
+        project.owner_type().to_string(),
+        project.owner_name().to_string(),
+      )
+      .into(),
+    ),
+    None => Err(queries::graphql_error(errors).into()),
+  }
+}
+
+fn is_access_error(error: &Error) -> bool {
+  let message = error.message.to_lowercase();
+  ACCESS_ERRORS.iter().any(|e| message.contains(e))
+}
+
+fn path_len(error: &Error) -> usize {
+  error
+    .path
+    .as_ref()
+    .map(|path| {
+      path
+        .iter()
+        .filter(|p| matches!(p, PathFragment::Key(_)))
+        .count()
+    })
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+  use std::str::FromStr;
+
+  use graphql_client::{Error, PathFragment};
+
+  use crate::errors::Errors;
+  use crate::models::{Project, ProjectId};
+
+  use super::classify;
+
+  const PROJECT_URL_ORG: &str = "https://github.com/orgs/JenSeReal/projects/3";
+
+  fn error(message: &str, path: &[&str]) -> Error {
+    Error {
+      message: message.to_string(),
+      locations: None,
+      path: Some(
+        path
+          .iter()
+          .map(|p| PathFragment::Key(p.to_string()))
+          .collect(),
+      ),
+      extensions: None,
+    }
+  }
+
+  #[test]
+  fn classify_found_project() -> anyhow::Result<(), anyhow::Error> {
+    let project = Project::from_str(PROJECT_URL_ORG)?;
+
+    assert_eq!(
+      classify(&project, Some(Some("PVT_kwDOA".to_string())), &[])?,
+      ProjectId::new("PVT_kwDOA".to_string())
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn classify_missing_owner() -> anyhow::Result<(), anyhow::Error> {
+    let project = Project::from_str(PROJECT_URL_ORG)?;
+    let errors = [error(
+      "Could not resolve to an Organization with the login of 'JenSeReal'.",
+      &["organization"],
+    )];
+
+    let err = classify(&project, None, &errors).unwrap_err();
+
+    assert!(matches!(
+      err.downcast_ref::<Errors>(),
+      Some(Errors::OwnerNotFound(_, _))
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn classify_missing_project() -> anyhow::Result<(), anyhow::Error> {
+    let project = Project::from_str(PROJECT_URL_ORG)?;
+    let errors = [error(
+      "Could not resolve to a ProjectV2 with the number 3.",
+      &["organization", "projectV2"],
+    )];
+
+    let err = classify(&project, Some(None), &errors).unwrap_err();
+
+    assert!(matches!(
+      err.downcast_ref::<Errors>(),
+      Some(Errors::ProjectNotFound(_, 3))
+    ));
+
+    Ok(())
+  }
+
+  #[test]
+  fn classify_no_access() -> anyhow::Result<(), anyhow::Error> {
+    let project = Project::from_str(PROJECT_URL_ORG)?;
+    let errors = [error(
+      "Resource not accessible by integration",
+      &["organization", "projectV2"],
+    )];
+
+    let err = classify(&project, Some(None), &errors).unwrap_err();
+
+    assert!(matches!(
+      err.downcast_ref::<Errors>(),
+      Some(Errors::NoProjectAccess(_, 3, _))
+    ));
+
+    Ok(())
+  }
+}
@@ -0,0 +1,316 @@
+use std::sync::Mutex;
+
+use anyhow::Context;
+use graphql_client::{GraphQLQuery, Response};
+use octocrab::Octocrab;
+
+use crate::models::{SingleSelectField, SingleSelectOption};
+use crate::{errors, item};
+
+pub use crate::models::Color as ProjectV2SingleSelectFieldOptionColor;
+
+/// The `URI` scalar of the schema.
+#[allow(clippy::upper_case_acronyms)]
+type URI = String;
+
+macro_rules! generate_query {
+  ($query:ident) => {
+    #[derive(GraphQLQuery)]
+    #[graphql(
+      schema_path = "schemas/github.graphql",
+      query_path = "schemas/queries.graphql",
+      extern_enums("ProjectV2SingleSelectFieldOptionColor"),
+      response_derives = "Debug",
+      skip_serializing_none
+    )]
+    pub struct $query;
+  };
+}
+
+generate_query!(GetProjectUser);
+generate_query!(GetProjectOrg);
+generate_query!(GetProjectFields);
+generate_query!(UpdateSingleSelectOptions);
+generate_query!(AddProjectItem);
+generate_query!(SetSingleSelectValue);
+generate_query!(GetPermission);
+generate_query!(AddReaction);
+generate_query!(AddComment);
+generate_query!(GetProjectItems);
+
+/// The GitHub API client. In a dry run every query is sent, but mutations are only recorded.
+#[derive(Debug)]
+pub struct Client {
+  crab: Octocrab,
+  dry_run: bool,
+  planned: Mutex<Vec<serde_json::Value>>,
+}
+
+impl Client {
+  pub fn new(crab: Octocrab, dry_run: bool) -> Self {
+    Self {
+      crab,
+      dry_run,
+      planned: Mutex::new(vec![]),
+    }
+  }
+
+  pub fn dry_run(&self) -> bool {
+    self.dry_run
+  }
+
+  /// The mutations that would have been sent in a dry run.
+  pub fn planned(&self) -> Vec<serde_json::Value> {
+    self
+      .planned
+      .lock()
+      .map(|planned| planned.clone())
+      .unwrap_or_default()
+  }
+}
+
+pub async fn send<Q: GraphQLQuery>(
+  client: &Client,
+  variables: Q::Variables,
+) -> anyhow::Result<Response<Q::ResponseData>, anyhow::Error> {
+  Ok(
+    client
+      .crab
+      .post("graphql", Some(&Q::build_query(variables)))
+      .await?,
+  )
+}
+
+/// Sends a GET request to the REST API, for data the GraphQL API can't filter by.
+pub async fn get<R: serde::de::DeserializeOwned, P: serde::Serialize>(
+  client: &Client,
+  route: &str,
+  parameters: &P,
+) -> anyhow::Result<R, anyhow::Error> {
+  Ok(client.crab.get(route, Some(parameters)).await?)
+}
+
+/// Sends a GET request to the REST API and returns only the status code, e.g. to tell deleted
+/// issues (410) from ones the token can't see (404).
+pub async fn status(client: &Client, route: &str) -> anyhow::Result<u16, anyhow::Error> {
+  let url = client.crab.absolute_url(route)?;
+  let response = client.crab._get(url, None::<&()>).await?;
+
+  Ok(response.status().as_u16())
+}
+
+pub fn graphql_error(errors: &[graphql_client::Error]) -> errors::Errors {
+  errors::Errors::GraphQl(
+    errors
+      .iter()
+      .map(|e| e.message.clone())
+      .collect::<Vec<_>>()
+      .join(", "),
+  )
+}
+
+pub async fn post<Q: GraphQLQuery>(
+  client: &Client,
+  variables: Q::Variables,
+) -> anyhow::Result<Q::ResponseData, anyhow::Error> {
+  let response = send::<Q>(client, variables).await?;
+
+  match response.errors {
+    Some(errors) if !errors.is_empty() => Err(graphql_error(&errors).into()),
+    _ => response.data.context("GitHub returned an empty response"),
+  }
+}
+
+/// Sends a mutation, or prints it and returns `None` in a dry run.
+pub async fn mutate<Q: GraphQLQuery>(
+  client: &Client,
+  variables: Q::Variables,
+) -> anyhow::Result<Option<Q::ResponseData>, anyhow::Error> {
+  if !client.dry_run {
+    return post::<Q>(client, variables).await.map(Some);
+  }
+
+  let mutation = serde_json::to_value(Q::build_query(variables))?;
+  println!(
+    "Dry run, not sending mutation:\n{}",
+    serde_json::to_string_pretty(&mutation)?
+  );
+
+  if let Ok(mut planned) = client.planned.lock() {
+    planned.push(mutation);
+  }
+
+  Ok(None)
+}
+
+/// Replaces the options of the field. GitHub recreates every option of the field, which clears
+/// it on every project item, so `options` has to contain every option that should be kept.
+/// Existing options that are neither in `options` nor in `removed` are refused before anything is
+/// sent, and GitHub dropping any of them fails after. The items are listed before the update and
+/// set to the recreated option with the same name after it. In a dry run the field is returned as
+/// it would be after the update, with the ids of the existing options.
+pub async fn update_single_select_options(
+  client: &Client,
+  field: &SingleSelectField,
+  options: &[SingleSelectOption],
+  removed: &[SingleSelectOption],
+) -> anyhow::Result<SingleSelectField, anyhow::Error> {
+  use update_single_select_options::*;
+
+  let omitted = field
+    .options()
+    .iter()
+    .filter(|existing| {
+      existing.id().map_or(false, |id| {
+        !options.iter().chain(removed).any(|o| o.id() == Some(id))
+      })
+    })
+    .map(|o| o.name().to_string())
+    .collect::<Vec<_>>();
+
+  if !omitted.is_empty() {
+    return Err(errors::Errors::OptionsOmitted(field.name().to_string(), omitted).into());
+  }
+
+  let items = match client.dry_run() {
+    true => vec![],
+    false => item::list_with_value(client, field.project_id(), field).await?,
+  };
+
+  let data = mutate::<UpdateSingleSelectOptions>(
+    client,
+    Variables {
+      field_id: field.id().to_string(),
+      options: options
+        .iter()
+        .map(|o| ProjectV2SingleSelectFieldOptionInput {
+          name: o.name().to_string(),
+          color: *o.color(),
+          description: o.description().to_string(),
+        })
+        .collect(),
+    },
+  )
+  .await?;
+
+  let Some(data) = data else {
+    return Ok(SingleSelectField::new(
+      field.project_id().clone(),
+      field.id().to_string(),
+      field.name().to_string(),
+      options.to_vec(),
+    ));
+  };
+
+  let updated = match data
+    .update_project_v2_field
+    .and_then(|payload| payload.project_v2_field)
+  {
+    Some(
+      UpdateSingleSelectOptionsUpdateProjectV2FieldProjectV2Field::ProjectV2SingleSelectField(
+        updated,
+      ),
+    ) => SingleSelectField::new(
+      field.project_id().clone(),
+      updated.id,
+      updated.name,
+      updated
+        .options
+        .into_iter()
+        .map(|o| SingleSelectOption::new(Some(o.id), o.name, o.color, o.description))
+        .collect(),
+    ),
+    _ => anyhow::bail!("Could not update the options of field {}", field.name()),
+  };
+
+  let recreated =
+    |option: &SingleSelectOption| updated.options().iter().find(|u| u.name() == option.name());
+
+  let dropped = options
+    .iter()
+    .filter(|o| o.id().is_some() && recreated(o).is_none())
+    .map(|o| o.name().to_string())
+    .collect::<Vec<_>>();
+
+  if !dropped.is_empty() {
+    return Err(errors::Errors::OptionsDropped(field.name().to_string(), dropped).into());
+  }
+
+  for project_item in items {
+    let Some(option_id) = options
+      .iter()
+      .find(|o| o.id() == Some(project_item.option_id()))
+      .and_then(recreated)
+      .and_then(SingleSelectOption::id)
+      .filter(|id| *id != project_item.option_id())
+    else {
+      continue;
+    };
+
+    item::set_value(client, &updated, project_item.id(), option_id)
+      .await
+      .context(format!(
+        "Could not restore option \"{}\" of item \"{}\"",
+        project_item.option_name(),
+        project_item.title()
+      ))?;
+  }
+
+  Ok(updated)
+}
+
+#[cfg(test)]
+mod test {
+  use crate::errors::Errors;
+  use crate::fake_github::FakeGitHub;
+  use crate::fixtures::{field, issue, option};
+  use crate::models::{Color, SingleSelectOption};
+
+  use super::{update_single_select_options, Client};
+
+  #[tokio::test]
+  async fn dry_run_records_mutations() -> anyhow::Result<(), anyhow::Error> {
+    let client = Client::new(octocrab::OctocrabBuilder::new().build()?, true);
+    let options = vec![SingleSelectOption::for_issue(&issue(&["epic"]), &[])];
+
+    let updated = update_single_select_options(&client, &field(vec![]), &options, &[]).await?;
+
+    assert_eq!(updated.options(), &options);
+    assert_eq!(client.planned().len(), 1);
+    assert_eq!(
+      client.planned()[0]["variables"],
+      serde_json::json!({
+        "fieldId": "PVTSSF_epic",
+        "options": [{
+          "name": "Test19",
+          "color": "GRAY",
+          "description": "JenSeReal/example-add-epic-issue-to-project#19 issue-id:1611401201 title:\"Test19\""
+        }]
+      })
+    );
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn refuse_to_omit_existing_options() -> anyhow::Result<(), anyhow::Error> {
+    let roadmap = option(Some("a"), "Roadmap", Color::Gray, None);
+    let field = field(vec![
+      roadmap.clone(),
+      option(Some("b"), "Search", Color::Gray, None),
+    ]);
+    let github = FakeGitHub::start(vec![]).await?;
+
+    let error = update_single_select_options(&github.client()?, &field, &[roadmap], &[])
+      .await
+      .unwrap_err();
+
+    assert!(matches!(
+      error.downcast_ref::<Errors>(),
+      Some(Errors::OptionsOmitted(_, omitted)) if omitted == &vec!["Search".to_string()]
+    ));
+    assert!(github.requests().is_empty());
+
+    Ok(())
+  }
+}
@@ -0,0 +1,620 @@
+use std::{collections::HashSet, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{ClosePolicy, Color, Issue, Params, SingleSelectField, SingleSelectOption};
+use crate::output::Outputs;
+use crate::queries::{self, Client};
+use crate::repositories;
+use crate::summary::Summary;
+use crate::{field, issues, project};
+
+/// A difference between the desired and the actual options of the field.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+  Add {
+    option: SingleSelectOption,
+  },
+  Rename {
+    from: SingleSelectOption,
+    to: String,
+  },
+  Recolor {
+    from: SingleSelectOption,
+    to: Color,
+  },
+  Remove {
+    option: SingleSelectOption,
+  },
+  /// The options of the epics, in their new order.
+  Reorder {
+    order: Vec<String>,
+  },
+}
+
+impl Display for Change {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Change::Add { option } => write!(f, "add \"{}\"", option.name()),
+      Change::Rename { from, to } => write!(f, "rename \"{}\" to \"{to}\"", from.name()),
+      Change::Recolor { from, to } => {
+        write!(
+          f,
+          "recolor \"{}\" from {} to {to}",
+          from.name(),
+          from.color()
+        )
+      }
+      Change::Remove { option } => write!(f, "remove \"{}\"", option.name()),
+      Change::Reorder { order } => write!(
+        f,
+        "reorder to {}",
+        order
+          .iter()
+          .map(|n| format!("\"{n}\""))
+          .collect::<Vec<_>>()
+          .join(", ")
+      ),
+    }
+  }
+}
+
+/// The changes that turn the actual options of the field into the desired ones, and the options
+/// the field has after all of them are applied.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Diff {
+  changes: Vec<Change>,
+  options: Vec<SingleSelectOption>,
+}
+
+impl Diff {
+  /// Options are matched by the issue id in their description. Options without one were not
+  /// created by this action and keep their place. Options of other issues are only removed if
+  /// their issue is in `removable`, i.e. known to be deleted or no longer an epic, and keep their
+  /// place otherwise, like duplicates do. The options of epics are ordered like `desired`.
+  pub fn compute(
+    actual: &[SingleSelectOption],
+    desired: &[SingleSelectOption],
+    removable: &HashSet<u64>,
+  ) -> Self {
+    let position = |option: &SingleSelectOption| {
+      desired
+        .iter()
+        .position(|d| d.issue_id() == option.issue_id())
+    };
+    let mut changes = vec![];
+    let mut options = vec![];
+    let mut seen = HashSet::new();
+
+    for option in actual {
+      let Some(issue_id) = option.issue_id() else {
+        options.push(option.clone());
+        continue;
+      };
+
+      match position(option).filter(|_| seen.insert(issue_id)) {
+        Some(i) => {
+          let wanted = option.reconciled(&desired[i]);
+          if wanted.name() != option.name() {
+            changes.push(Change::Rename {
+              from: option.clone(),
+              to: wanted.name().to_string(),
+            });
+          }
+          if wanted.color() != option.color() {
+            changes.push(Change::Recolor {
+              from: option.clone(),
+              to: *wanted.color(),
+            });
+          }
+          options.push(wanted);
+        }
+        None if removable.contains(&issue_id) => changes.push(Change::Remove {
+          option: option.clone(),
+        }),
+        None => options.push(option.clone()),
+      }
+    }
+
+    for wanted in desired {
+      if wanted.issue_id().map_or(false, |id| seen.insert(id)) {
+        changes.push(Change::Add {
+          option: wanted.clone(),
+        });
+        options.push(wanted.clone());
+      }
+    }
+
+    let slots = options
+      .iter()
+      .enumerate()
+      .filter(|(_, o)| position(o).is_some())
+      .map(|(i, _)| i)
+      .collect::<Vec<_>>();
+    let mut epics = slots
+      .iter()
+      .map(|i| options[*i].clone())
+      .collect::<Vec<_>>();
+    let existing = |epics: &[SingleSelectOption]| {
+      epics
+        .iter()
+        .filter(|o| o.id().is_some())
+        .map(|o| o.issue_id())
+        .collect::<Vec<_>>()
+    };
+
+    let before = existing(&epics);
+    epics.sort_by_key(|o| position(o));
+    if existing(&epics) != before {
+      changes.push(Change::Reorder {
+        order: epics.iter().map(|o| o.name().to_string()).collect(),
+      });
+    }
+
+    for (slot, option) in slots.into_iter().zip(epics) {
+      options[slot] = option;
+    }
+
+    Self { changes, options }
+  }
+
+  pub fn changes(&self) -> &Vec<Change> {
+    &self.changes
+  }
+
+  pub fn options(&self) -> &Vec<SingleSelectOption> {
+    &self.options
+  }
+
+  /// The options the diff removes.
+  pub fn removed(&self) -> Vec<SingleSelectOption> {
+    self
+      .changes
+      .iter()
+      .filter_map(|change| match change {
+        Change::Remove { option } => Some(option.clone()),
+        _ => None,
+      })
+      .collect()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+}
+
+/// Reconciles the options of the field with all epics of the repositories, see
+/// [`repositories::resolve`].
+pub async fn run(
+  params: &Params,
+  client: &Client,
+  outputs: &mut Outputs,
+  summary: &mut Summary,
+) -> anyhow::Result<(), anyhow::Error> {
+  let repositories = repositories::resolve(params, client).await?;
+
+  let project_id = project::resolve(client, params.project()).await?;
+  outputs.set("project-id", &project_id);
+  summary.project(params, &project_id);
+
+  let field = field::find(client, &project_id, params.field_name()).await?;
+  outputs.set("field-id", field.id());
+  summary.field(&field);
+
+  let diff = desired_diff(params, client, &repositories, &field).await?;
+  outputs.set("changes", diff.changes().len());
+  summary.diff(&diff);
+
+  for change in diff.changes() {
+    println!("{change}");
+  }
+
+  if diff.is_empty() {
+    println!(
+      "Field \"{}\" is in sync with the epics of {}",
+      field.name(),
+      repositories.join(", ")
+    );
+    return Ok(());
+  }
+
+  apply(client, &field, &diff).await?;
+  println!(
+    "Applied {} changes to field \"{}\"",
+    diff.changes().len(),
+    field.name()
+  );
+
+  Ok(())
+}
+
+/// The diff between the options of the field and the options of the epics of the repositories.
+/// Options of issues in other repositories are neither desired nor removable, so they are kept as
+/// they are.
+pub async fn desired_diff(
+  params: &Params,
+  client: &Client,
+  repositories: &[String],
+  field: &SingleSelectField,
+) -> anyhow::Result<Diff, anyhow::Error> {
+  let issues = repositories::list_issues(client, repositories).await?;
+  let desired = desired_options(params, &issues, field.options());
+  let removable = removable_issues(client, repositories, field, &issues, &desired).await?;
+
+  Ok(Diff::compute(field.options(), &desired, &removable))
+}
+
+/// The ids of the issues whose options can be removed: listed issues that are no longer epics or
+/// closed epics whose option [`ClosePolicy::Remove`] removes, and deleted issues of the
+/// repositories. Issues that are neither listed nor deleted may be hidden from the token, so their
+/// options are kept.
+async fn removable_issues(
+  client: &Client,
+  repositories: &[String],
+  field: &SingleSelectField,
+  issues: &[Issue],
+  desired: &[SingleSelectOption],
+) -> anyhow::Result<HashSet<u64>, anyhow::Error> {
+  let desired = desired
+    .iter()
+    .filter_map(|o| o.issue_id())
+    .collect::<HashSet<_>>();
+  let listed = issues.iter().map(Issue::id).collect::<HashSet<_>>();
+  let mut removable = listed.difference(&desired).copied().collect::<HashSet<_>>();
+
+  for option in field.options() {
+    let (Some(issue_id), Some(number)) = (option.issue_id(), option.issue_number()) else {
+      continue;
+    };
+    if listed.contains(&issue_id) || !option.is_in(repositories) {
+      continue;
+    }
+
+    // Options without a repository are in the only one, see `SingleSelectOption::is_in`.
+    let repository = option.repository().unwrap_or(&repositories[0]);
+    if issues::is_deleted(client, repository, number).await? {
+      removable.insert(issue_id);
+    }
+  }
+
+  Ok(removable)
+}
+
+/// The options the epics among the issues should have, named like
+/// [`SingleSelectOption::for_issue`] against each other and the `existing` options of issues that
+/// weren't listed, e.g. of other repositories. Issues that stopped being epics keep their
+/// existing option as the unlabeled policy marks it, unless it is [`ClosePolicy::Remove`].
+pub fn desired_options(
+  params: &Params,
+  issues: &[Issue],
+  existing: &[SingleSelectOption],
+) -> Vec<SingleSelectOption> {
+  let listed = issues.iter().map(Issue::id).collect::<HashSet<_>>();
+  let desired = |issue: &Issue, others: &[SingleSelectOption]| match params.is_epic(issue) {
+    true => SingleSelectOption::desired(issue, params.on_close(), others),
+    false
+      if params.on_unlabeled() != &ClosePolicy::Remove
+        && existing.iter().any(|o| o.issue_id() == Some(issue.id())) =>
+    {
+      Some(SingleSelectOption::for_issue(issue, others).closed(params.on_unlabeled()))
+    }
+    false => None,
+  };
+  let epics = issues
+    .iter()
+    .filter(|i| desired(i, &[]).is_some())
+    .collect::<Vec<_>>();
+  let others = existing
+    .iter()
+    .filter(|o| o.issue_id().map_or(false, |id| !listed.contains(&id)))
+    .cloned()
+    .chain(epics.iter().map(|i| SingleSelectOption::for_issue(i, &[])))
+    .collect::<Vec<_>>();
+
+  epics
+    .into_iter()
+    .filter_map(|i| desired(i, &others))
+    .collect()
+}
+
+/// Applies all changes of the diff with one field update.
+pub async fn apply(
+  client: &Client,
+  field: &SingleSelectField,
+  diff: &Diff,
+) -> anyhow::Result<SingleSelectField, anyhow::Error> {
+  if diff.options().is_empty() {
+    anyhow::bail!(
+      "Could not apply the changes, GitHub requires field \"{}\" to keep at least one option",
+      field.name()
+    );
+  }
+
+  queries::update_single_select_options(client, field, diff.options(), &diff.removed()).await
+}
+
+#[cfg(test)]
+mod test {
+  use std::collections::HashSet;
+
+  use crate::fake_github::FakeGitHub;
+  use crate::fixtures::{field, issue_json, issue_with, option, params, REPOSITORY};
+  use crate::models::{ClosePolicy, Color, Issue, SingleSelectOption};
+
+  use super::{Change, Diff};
+
+  #[test]
+  fn diff_in_sync() {
+    let actual = vec![
+      option(Some("a"), "No epic", Color::Gray, None),
+      option(Some("b"), "Roadmap", Color::Gray, Some((101, 1))),
+    ];
+    let desired = vec![option(None, "Roadmap", Color::Gray, Some((101, 1)))];
+
+    let diff = Diff::compute(&actual, &desired, &HashSet::new());
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.options(), &actual);
+  }
+
+  #[test]
+  fn diff_every_change() {
+    let actual = vec![
+      option(Some("a"), "Search", Color::Gray, Some((103, 3))),
+      option(Some("b"), "No epic", Color::Red, None),
+      option(Some("c"), "Roadmap", Color::Gray, Some((101, 1))),
+      option(Some("d"), "Gone", Color::Gray, Some((102, 2))),
+      option(Some("e"), "Search copy", Color::Gray, Some((103, 3))),
+      option(Some("f"), "Elsewhere", Color::Gray, Some((105, 5))),
+    ];
+    let desired = vec![
+      option(None, "Roadmap 2024", Color::Gray, Some((101, 1))),
+      option(None, "Search", Color::Gray, Some((103, 3))).closed(&ClosePolicy::Recolor),
+      option(None, "Login", Color::Gray, Some((104, 4))),
+    ];
+
+    let diff = Diff::compute(&actual, &desired, &HashSet::from([102]));
+
+    assert_eq!(
+      diff.changes(),
+      &vec![
+        Change::Recolor {
+          from: actual[0].clone(),
+          to: Color::Purple
+        },
+        Change::Rename {
+          from: actual[2].clone(),
+          to: "Roadmap 2024".to_string()
+        },
+        Change::Remove {
+          option: actual[3].clone()
+        },
+        Change::Add {
+          option: desired[2].clone()
+        },
+        Change::Reorder {
+          order: vec![
+            "Roadmap 2024".to_string(),
+            "Search".to_string(),
+            "Search copy".to_string(),
+            "Login".to_string()
+          ]
+        },
+      ]
+    );
+    assert_eq!(
+      diff.options(),
+      &vec![
+        option(Some("c"), "Roadmap 2024", Color::Gray, Some((101, 1))),
+        actual[1].clone(),
+        option(Some("a"), "Search", Color::Gray, Some((103, 3))).closed(&ClosePolicy::Recolor),
+        actual[4].clone(),
+        actual[5].clone(),
+        desired[2].clone(),
+      ]
+    );
+    assert_eq!(
+      diff.changes()[1].to_string(),
+      "rename \"Roadmap\" to \"Roadmap 2024\""
+    );
+  }
+
+  #[test]
+  fn diff_keeps_custom_names_and_colors() {
+    let epic = |title: &str, state: &str| issue_with(101, 1, title, &["epic"], state);
+    let actual = vec![SingleSelectOption::new(
+      Some("a".to_string()),
+      "Roadmap 2024".to_string(),
+      Color::Green,
+      SingleSelectOption::for_issue(&epic("Roadmap", "open"), &[])
+        .description()
+        .to_string(),
+    )];
+    let diff = |title: &str, state: &str, policy: &ClosePolicy| {
+      let desired = SingleSelectOption::desired(&epic(title, state), policy, &[]);
+      Diff::compute(&actual, &Vec::from_iter(desired), &HashSet::new())
+    };
+
+    let unchanged = diff("Roadmap", "open", &ClosePolicy::Recolor);
+    assert!(unchanged.is_empty());
+    assert_eq!(unchanged.options(), &actual);
+
+    let retitled = diff("Roadmap 2025", "open", &ClosePolicy::Recolor);
+    assert_eq!(
+      retitled.changes(),
+      &vec![Change::Rename {
+        from: actual[0].clone(),
+        to: "Roadmap 2025".to_string()
+      }]
+    );
+    assert_eq!(retitled.options()[0].color(), &Color::Green);
+
+    let closed = diff("Roadmap", "closed", &ClosePolicy::RenameWithSuffix);
+    assert_eq!(
+      closed.changes(),
+      &vec![Change::Rename {
+        from: actual[0].clone(),
+        to: "✅ Roadmap 2024".to_string()
+      }]
+    );
+
+    let recolored = diff("Roadmap", "closed", &ClosePolicy::Recolor);
+    assert_eq!(
+      recolored.changes(),
+      &vec![Change::Recolor {
+        from: actual[0].clone(),
+        to: Color::Purple
+      }]
+    );
+    assert_eq!(recolored.options()[0].name(), "Roadmap 2024");
+  }
+
+  #[test]
+  fn apply_unlabeled_policy_to_former_epics() {
+    let mut inputs = vec!["epic"];
+    inputs.resize(14, "");
+    inputs.push("recolor");
+    let params = params(&inputs);
+    let issues = [
+      issue_with(101, 1, "Roadmap", &["bug"], "open"),
+      issue_with(102, 2, "Search", &["bug"], "open"),
+    ];
+    let existing = vec![option(Some("a"), "Roadmap", Color::Green, Some((101, 1)))];
+
+    let desired = super::desired_options(&params, &issues, &existing);
+
+    assert_eq!(desired.len(), 1);
+    assert_eq!(desired[0].issue_id(), Some(101));
+    assert_eq!(desired[0].color(), &Color::Purple);
+    assert_eq!(
+      Diff::compute(&existing, &desired, &HashSet::from([102])).changes(),
+      &vec![Change::Recolor {
+        from: existing[0].clone(),
+        to: Color::Purple
+      }]
+    );
+  }
+
+  #[test]
+  fn disambiguate_colliding_names_across_repositories() -> anyhow::Result<(), anyhow::Error> {
+    let params = params(&["epic"]);
+    let issue = |repository: &str, id: u64, title: &str| -> Issue {
+      let number = id % 100;
+      let mut issue = issue_json(id, number as u32, title, &["epic"], "open");
+      issue["html_url"] =
+        format!("https://github.com/JenSeReal/{repository}/issues/{number}").into();
+      serde_json::from_value(issue).unwrap()
+    };
+    let other = SingleSelectOption::new(
+      Some("a".to_string()),
+      "docs#4 Search".to_string(),
+      Color::Gray,
+      "JenSeReal/docs#4 issue-id:304".to_string(),
+    );
+
+    let desired = super::desired_options(
+      &params,
+      &[
+        issue("web", 101, "Login"),
+        issue("web", 102, "Search"),
+        issue("api", 203, "login"),
+        issue("api", 205, "Roadmap"),
+      ],
+      &[other],
+    );
+
+    assert_eq!(
+      desired.iter().map(|o| o.name()).collect::<Vec<_>>(),
+      ["web#1 Login", "web#2 Search", "api#3 login", "Roadmap"]
+    );
+    assert_eq!(desired[2].issue_id(), Some(203));
+    assert_eq!(desired[2].repository(), Some("JenSeReal/api"));
+
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn keep_options_of_pull_requests_closed_epics_and_other_repositories(
+  ) -> anyhow::Result<(), anyhow::Error> {
+    let mut inputs = vec!["epic"];
+    inputs.resize(15, "");
+    // Sync lists closed issues regardless of the state input.
+    inputs.push("open");
+    let params = params(&inputs);
+    let issue = |id: u64, number: u32, label: &str, state: &str| {
+      issue_json(id, number, &format!("Issue {number}"), &[label], state)
+    };
+    let mut pull_request_issue = issue(7003, 3, "epic", "open");
+    pull_request_issue["pull_request"] = serde_json::json!({});
+
+    let github = FakeGitHub::start_with_status(vec![
+      (
+        200,
+        serde_json::json!([
+          issue(1, 1, "epic", "open"),
+          issue(2, 2, "epic", "closed"),
+          pull_request_issue,
+          issue(4, 4, "bug", "open"),
+        ]),
+      ),
+      (200, serde_json::json!([])),
+      (200, serde_json::json!([issue(900, 3, "epic", "open")])),
+      (200, serde_json::json!([])),
+      (
+        410,
+        serde_json::json!({ "message": "This issue was deleted" }),
+      ),
+      (404, serde_json::json!({ "message": "Not Found" })),
+    ])
+    .await?;
+    let actual = vec![
+      option(Some("a"), "Issue 1", Color::Gray, Some((1, 1))),
+      option(Some("b"), "Issue 2", Color::Gray, Some((2, 2))),
+      option(Some("c"), "Issue 3", Color::Gray, Some((900, 3))),
+      option(Some("d"), "Issue 4", Color::Gray, Some((4, 4))),
+      option(Some("e"), "Deleted", Color::Gray, Some((5, 5))),
+      option(Some("f"), "Hidden", Color::Gray, Some((6, 6))),
+      SingleSelectOption::new(
+        Some("g".to_string()),
+        "Elsewhere".to_string(),
+        Color::Gray,
+        "JenSeReal/other#7 issue-id:7".to_string(),
+      ),
+    ];
+    let field = field(actual.clone());
+
+    let diff = super::desired_diff(
+      &params,
+      &github.client()?,
+      &[REPOSITORY.to_string()],
+      &field,
+    )
+    .await?;
+
+    assert_eq!(
+      diff.changes(),
+      &vec![
+        Change::Remove {
+          option: actual[3].clone()
+        },
+        Change::Remove {
+          option: actual[4].clone()
+        },
+      ]
+    );
+    assert_eq!(
+      diff.options().iter().map(|o| o.id()).collect::<Vec<_>>(),
+      [Some("a"), Some("b"), Some("c"), Some("f"), Some("g")]
+    );
+    // The option of the other repository is neither looked up nor changed.
+    let paths = github.paths();
+    assert_eq!(paths.len(), 6);
+    assert!(paths[0].starts_with(&format!("/repos/{REPOSITORY}/issues?state=all")));
+    assert!(paths[2].starts_with(&format!("/repos/{REPOSITORY}/pulls?state=all")));
+    assert_eq!(paths[4], format!("/repos/{REPOSITORY}/issues/5"));
+    assert_eq!(paths[5], format!("/repos/{REPOSITORY}/issues/6"));
+
+    Ok(())
+  }
+}
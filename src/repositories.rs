@@ -0,0 +1,130 @@
+use std::env;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::issues;
+use crate::models::{Issue, OwnerType, Params, StateFilter};
+use crate::queries::{self, Client};
+
+const PER_PAGE: u32 = 100;
+/// The search API returns at most 1000 results.
+const MAX_SEARCH_PAGES: u32 = 10;
+
+#[derive(Serialize)]
+struct SearchRepositories {
+  q: String,
+  per_page: u32,
+  page: u32,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+  items: Vec<SearchItem>,
+}
+
+#[derive(Deserialize)]
+struct SearchItem {
+  full_name: String,
+}
+
+/// The full names of the repositories epics are collected from. These are the configured
+/// repositories and all repositories of the project owner with the configured topic, or the
+/// repository in `GITHUB_REPOSITORY` when neither is configured.
+pub async fn resolve(
+  params: &Params,
+  client: &Client,
+) -> anyhow::Result<Vec<String>, anyhow::Error> {
+  let owner = params.project().owner_name();
+  let mut repositories = params
+    .repositories()
+    .iter()
+    .map(|r| match r.contains('/') {
+      true => r.clone(),
+      false => format!("{owner}/{r}"),
+    })
+    .collect::<Vec<_>>();
+
+  if let Some(topic) = params.topic() {
+    repositories.extend(search(client, params.project().owner_type(), owner, topic).await?);
+  }
+
+  if repositories.is_empty() {
+    return Ok(vec![
+      env::var("GITHUB_REPOSITORY").context("Missing GITHUB_REPOSITORY")?
+    ]);
+  }
+
+  // Repository names are case insensitive.
+  let mut seen = vec![];
+  repositories.retain(|r| {
+    let key = r.to_lowercase();
+    let new = !seen.contains(&key);
+    seen.push(key);
+    new
+  });
+
+  Ok(repositories)
+}
+
+/// Searches all repositories of the owner with the topic.
+async fn search(
+  client: &Client,
+  owner_type: &OwnerType,
+  owner: &str,
+  topic: &str,
+) -> anyhow::Result<Vec<String>, anyhow::Error> {
+  let qualifier = match owner_type {
+    OwnerType::Organizations => "org",
+    OwnerType::Users => "user",
+  };
+  let mut repositories = vec![];
+
+  for page in 1..=MAX_SEARCH_PAGES {
+    let result: SearchResult = queries::get(
+      client,
+      "search/repositories",
+      &SearchRepositories {
+        q: format!("{qualifier}:{owner} topic:{topic}"),
+        per_page: PER_PAGE,
+        page,
+      },
+    )
+    .await?;
+
+    let found = result.items.len();
+    repositories.extend(result.items.into_iter().map(|item| item.full_name));
+    if found < PER_PAGE as usize {
+      break;
+    }
+  }
+
+  println!(
+    "Found {} repositories of {owner} with topic {topic}",
+    repositories.len()
+  );
+
+  Ok(repositories)
+}
+
+/// Lists all open and closed issues and pull requests of the repositories. Pull requests have
+/// their own id, like in `pull_request` events.
+pub async fn list_issues(
+  client: &Client,
+  repositories: &[String],
+) -> anyhow::Result<Vec<Issue>, anyhow::Error> {
+  let mut issues = vec![];
+
+  for repository in repositories {
+    let listed = issues::list_all(client, repository, &StateFilter::All)
+      .await
+      .context(format!("Could not list the issues of {repository}"))?;
+    let pull_requests = issues::list_pull_requests(client, repository)
+      .await
+      .context(format!("Could not list the pull requests of {repository}"))?;
+
+    issues.extend(listed.into_iter().chain(pull_requests));
+  }
+
+  Ok(issues)
+}
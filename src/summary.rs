@@ -0,0 +1,322 @@
+use std::{env, fmt::Write as _, fs::OpenOptions, io::Write};
+
+use crate::audit::Report;
+use crate::backfill::Progress;
+use crate::command::Command;
+use crate::models::{Issue, Operation, Params, ProjectId, SingleSelectField};
+use crate::plan::Plan;
+use crate::reconcile::Diff;
+
+/// Markdown job summary of the action, written to the file in `GITHUB_STEP_SUMMARY`.
+#[derive(Debug, Default)]
+pub struct Summary {
+  issue: Option<String>,
+  command: Option<String>,
+  backfill: Option<String>,
+  diff: Option<String>,
+  plan: Option<String>,
+  audit: Option<String>,
+  filter: Option<String>,
+  project: Option<String>,
+  field: Option<String>,
+  operation: Option<String>,
+  item: Option<String>,
+  planned: Option<String>,
+}
+
+impl Summary {
+  pub fn filter(&mut self, params: &Params, issue: &Issue, is_epic: bool) -> &mut Self {
+    self.issue = Some(format!(
+      "[#{} {}]({})",
+      issue.number(),
+      escape(issue.title()),
+      issue.html_url()
+    ));
+    self.filter = Some(format!(
+      "| {} | {} | `{}` | {} | {} |",
+      labels(params.labels().iter().map(String::as_str)),
+      params.operator(),
+      escape(&params.filter().to_string()),
+      labels(issue.labels().map(|l| l.name())),
+      if is_epic { "✅ epic" } else { "❌ no epic" }
+    ));
+    self
+  }
+
+  pub fn command(&mut self, issue: &Issue, command: &Command, login: &str) -> &mut Self {
+    self.issue = Some(format!(
+      "[#{} {}]({})",
+      issue.number(),
+      escape(issue.title()),
+      issue.html_url()
+    ));
+    self.command = Some(format!("`{command}` by @{login}"));
+    self
+  }
+
+  pub fn backfill(&mut self, repository: &str, progress: &Progress) -> &mut Self {
+    self.backfill = Some(format!(
+      "| {} | {} | {} | {} | {} |",
+      escape(repository),
+      progress.pages,
+      progress.issues,
+      progress.epics,
+      progress.created
+    ));
+    self
+  }
+
+  pub fn diff(&mut self, diff: &Diff) -> &mut Self {
+    self.diff = Some(
+      diff
+        .changes()
+        .iter()
+        .map(|change| format!("- {}\n", escape(&change.to_string())))
+        .collect(),
+    );
+    self
+  }
+
+  pub fn plan(&mut self, plan: &Plan) -> &mut Self {
+    self.plan = Some(plan.table());
+    self
+  }
+
+  pub fn audit(&mut self, report: &Report) -> &mut Self {
+    self.audit = Some(report.markdown());
+    self
+  }
+
+  pub fn project(&mut self, params: &Params, project_id: &ProjectId) -> &mut Self {
+    let project = params.project();
+    self.project = Some(format!(
+      "[{}/{}]({}) (`{}`)",
+      project.owner_name(),
+      project.number(),
+      project.url(),
+      project_id
+    ));
+    self
+  }
+
+  pub fn field(&mut self, field: &SingleSelectField) -> &mut Self {
+    self.field = Some(format!("{} (`{}`)", escape(field.name()), field.id()));
+    self
+  }
+
+  pub fn operation(&mut self, operation: &Operation) -> &mut Self {
+    let option = operation.option();
+    self.operation = Some(format!(
+      "**{}** option \"{}\"{}",
+      operation,
+      escape(option.name()),
+      option
+        .id()
+        .map(|id| format!(" (`{id}`)"))
+        .unwrap_or_default()
+    ));
+    self
+  }
+
+  pub fn item(&mut self, item_id: &str) -> &mut Self {
+    self.item = Some(format!("`{item_id}`"));
+    self
+  }
+
+  pub fn planned(
+    &mut self,
+    planned: &[serde_json::Value],
+  ) -> anyhow::Result<&mut Self, anyhow::Error> {
+    let mut markdown = String::new();
+    for mutation in planned {
+      let _ = writeln!(
+        markdown,
+        "```json\n{}\n```\n",
+        serde_json::to_string_pretty(mutation)?
+      );
+    }
+
+    self.planned = Some(markdown);
+    Ok(self)
+  }
+
+  /// Appends the summary to `GITHUB_STEP_SUMMARY`. Does nothing when not running in GitHub
+  /// Actions.
+  pub fn write(&self) -> anyhow::Result<(), anyhow::Error> {
+    let Ok(path) = env::var("GITHUB_STEP_SUMMARY") else {
+      return Ok(());
+    };
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(self.render().as_bytes())?;
+
+    Ok(())
+  }
+
+  fn render(&self) -> String {
+    let mut markdown = String::from("## add-epic-to-project\n\n");
+
+    if let Some(issue) = &self.issue {
+      let _ = writeln!(markdown, "Issue {issue}\n");
+    }
+
+    if let Some(command) = &self.command {
+      let _ = writeln!(markdown, "### Command\n\n{command}\n");
+    }
+
+    if let Some(filter) = &self.filter {
+      let _ = writeln!(
+        markdown,
+        "### Label filter\n\n| Configured labels | Operator | Expression | Issue labels | Result |\n| --- | --- | --- | --- | --- |\n{filter}\n"
+      );
+    }
+
+    if self.project.is_some() || self.field.is_some() {
+      let _ = writeln!(
+        markdown,
+        "### Project\n\n| Project | Field |\n| --- | --- |\n| {} | {} |\n",
+        self.project.as_deref().unwrap_or("-"),
+        self.field.as_deref().unwrap_or("-")
+      );
+    }
+
+    if let Some(backfill) = &self.backfill {
+      let _ = writeln!(
+        markdown,
+        "### Backfill\n\n| Repository | Pages | Issues | Epics | Created options |\n| --- | --- | --- | --- | --- |\n{backfill}\n"
+      );
+    }
+
+    if let Some(diff) = &self.diff {
+      let _ = writeln!(
+        markdown,
+        "### Sync\n\n{}",
+        if diff.is_empty() {
+          "The field is in sync\n"
+        } else {
+          diff
+        }
+      );
+    }
+
+    if let Some(plan) = &self.plan {
+      let _ = writeln!(markdown, "### Plan\n\n{plan}");
+    }
+
+    if let Some(audit) = &self.audit {
+      let _ = writeln!(markdown, "### Audit\n\n{audit}");
+    }
+
+    if let Some(operation) = &self.operation {
+      let _ = writeln!(markdown, "### Mutation\n\n{operation}\n");
+    }
+
+    if let Some(item) = &self.item {
+      let _ = writeln!(markdown, "The epic issue is project item {item}\n");
+    }
+
+    if let Some(planned) = &self.planned {
+      let _ = writeln!(
+        markdown,
+        "### Dry run\n\nThe following mutations were not sent:\n\n{planned}"
+      );
+    }
+
+    markdown
+  }
+}
+
+fn labels<'a>(labels: impl Iterator<Item = &'a str>) -> String {
+  let labels = labels
+    .map(|l| format!("`{}`", escape(l)))
+    .collect::<Vec<_>>();
+
+  if labels.is_empty() {
+    "-".to_string()
+  } else {
+    labels.join(", ")
+  }
+}
+
+fn escape(s: &str) -> String {
+  s.replace('|', "\\|")
+    .replace('[', "\\[")
+    .replace(']', "\\]")
+}
+
+#[cfg(test)]
+mod test {
+  use crate::fixtures::params;
+  use crate::models::{IssueEvent, Operation, ProjectId, SingleSelectOption};
+
+  use super::Summary;
+
+  const ISSUE_EVENT: &str = r#"{
+    "action": "opened",
+    "issue": {
+      "id": 1611401201,
+      "node_id": "I_kwDOJEXPh85gDAfx",
+      "labels": [{ "id": 1, "name": "epic" }],
+      "number": 19,
+      "title": "Test19",
+      "html_url": "https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/19"
+    }
+  }"#;
+
+  #[test]
+  fn render_summary() -> anyhow::Result<(), anyhow::Error> {
+    let params = params(&["epic, initiative"]);
+    let event: IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+
+    let mut summary = Summary::default();
+    summary
+      .filter(&params, event.issue(), true)
+      .project(&params, &ProjectId::new("PVT_kwDOA".to_string()))
+      .operation(&Operation::Created(SingleSelectOption::for_issue(
+        event.issue(),
+        &[],
+      )));
+
+    assert_eq!(
+      summary.render(),
+      "## add-epic-to-project
+
+Issue [#19 Test19](https://github.com/JenSeReal/example-add-epic-issue-to-project/issues/19)
+
+### Label filter
+
+| Configured labels | Operator | Expression | Issue labels | Result |
+| --- | --- | --- | --- | --- |
+| `epic`, `initiative` | OR | `epic OR initiative` | `epic` | ✅ epic |
+
+### Project
+
+| Project | Field |
+| --- | --- |
+| [JenSeReal/3](https://github.com/orgs/JenSeReal/projects/3) (`PVT_kwDOA`) | - |
+
+### Mutation
+
+**created** option \"Test19\"
+
+"
+    );
+
+    Ok(())
+  }
+
+  #[test]
+  fn escape_pipes_in_label_filter() -> anyhow::Result<(), anyhow::Error> {
+    let params = params(&["/^(epic|initiative)$/i"]);
+    let event: IssueEvent = serde_json::from_str(ISSUE_EVENT)?;
+
+    let mut summary = Summary::default();
+    summary.filter(&params, event.issue(), true);
+
+    assert!(summary.render().contains(
+      "| `/^(epic\\|initiative)$/i` | OR | `/^(epic\\|initiative)$/i` | `epic` | ✅ epic |"
+    ));
+
+    Ok(())
+  }
+}